@@ -1,27 +1,102 @@
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use handlebars::Handlebars;
+use i18n::Localised;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use url::Url;
 
+#[path = "src/canonical.rs"]
+mod canonical;
+// The site picks a locale in the browser, the build only writes a page for each
+#[path = "src/i18n.rs"]
+#[allow(dead_code)]
+mod i18n;
+
+// Keep in sync with sites.schema.json, which editors use to check the file as it's written
 #[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Site {
     url: String,
     me: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fediverse: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<Localised>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<Localised>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order: Option<i32>,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    valid_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    valid_until: Option<String>,
+    /// Whether the site isn't hidden and is valid on the day of the build, only those get rel=me.
+    #[serde(skip_deserializing)]
+    shown: bool,
 }
 
 #[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct UserInfoResponse {
-    name: String,
+    #[serde(rename = "$schema", default, skip_serializing)]
+    _schema: Option<String>,
+    name: Localised,
+    #[serde(default, alias = "handles", skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
     homepage: String,
+    source: String,
     sites: Vec<Site>,
-    last_modified: Option<String>,
+}
+
+/// What the index template shows, for one of the locales or for the page at the root of the site.
+#[derive(Serialize)]
+struct Page<'a> {
+    lang: &'a str,
+    /// Only set on the per-locale pages, at the root the site picks one from the browser's languages.
+    locale: Option<&'a str>,
+    title: String,
+    no_wasm: &'a str,
+    no_wasm_title: &'a str,
+    homepage: &'a str,
+    sites: &'a [Site],
+    alternates: &'a [Alternate],
+}
+
+/// One of the index pages, as listed in hreflang links.
+#[derive(Serialize)]
+struct Alternate {
+    hreflang: String,
+    href: String,
+}
+
+#[derive(Serialize)]
+struct Sitemap<'a> {
+    last_modified: String,
+    alternates: &'a [Alternate],
+}
+
+/// Something wrong with a value in sites.json.
+struct Problem {
+    /// The value as it's written in the file, to find where it is.
+    value: String,
+    message: String,
 }
 
 const STATIC_DIR: &str = "static";
+// Dates in sites.json, which the site compares as text
+const DATE_FORMAT: &str = "%Y-%m-%d";
 
 fn main() -> io::Result<()> {
     const CUSTOM_SITES: &str = "assets/sites.json";
@@ -44,24 +119,78 @@ fn main() -> io::Result<()> {
     let mut handlebars = Handlebars::new();
     handlebars.set_strict_mode(true);
 
-    let json = read_file(&[STATIC_DIR, "sites.json"]).unwrap();
-    let mut user_info: UserInfoResponse = serde_json::from_str(&json).unwrap();
-    user_info.last_modified = Some(Utc::now().to_rfc3339());
+    let json = read_file(&[STATIC_DIR, "sites.json"])?;
+    let mut user_info: UserInfoResponse = serde_json::from_str(&json).map_err(|error| {
+        // The message ends with the position, which we already show at the start
+        let position = format!(" at line {} column {}", error.line(), error.column());
+        let message = error.to_string();
+        eprintln!(
+            "{}:{}:{}: {}",
+            sites_to_use,
+            error.line(),
+            error.column(),
+            message.strip_suffix(&position).unwrap_or(&message)
+        );
+        invalid_sites(&format!("{} isn't valid", sites_to_use))
+    })?;
 
-    render_template_to_file(
-        &mut handlebars,
-        "html",
-        "index.html.handlebars",
-        "index.html",
-        &user_info,
-    );
+    let problems = check_sites(&user_info);
+    if !problems.is_empty() {
+        for problem in &problems {
+            let (line, column) = locate(&json, &problem.value);
+            eprintln!("{}:{}:{}: {}", sites_to_use, line, column, problem.message);
+        }
+        return Err(invalid_sites(&format!(
+            "{} has {} problem(s)",
+            sites_to_use,
+            problems.len()
+        )));
+    }
+
+    let today = Utc::now().date().naive_utc();
+    for site in &mut user_info.sites {
+        site.shown = !site.hidden
+            && parse_date(&site.valid_from).map_or(true, |from| from <= today)
+            && parse_date(&site.valid_until).map_or(true, |until| today <= until);
+    }
+    warn_about_duplicates(&user_info);
+
+    let alternates = alternates(&user_info.homepage);
+
+    // The root page is in the default locale until the site picks one
+    let locales = i18n::LOCALES.iter().map(|locale| Some(*locale));
+    for locale in std::iter::once(None).chain(locales) {
+        let lang = locale.unwrap_or(i18n::DEFAULT_LOCALE);
+        let messages = i18n::messages(lang);
+        let page = Page {
+            lang,
+            locale,
+            title: i18n::title(lang, user_info.name.get(lang).unwrap_or_default()),
+            no_wasm: messages.no_wasm,
+            no_wasm_title: messages.no_wasm_title,
+            homepage: &user_info.homepage,
+            sites: &user_info.sites,
+            alternates: &alternates,
+        };
+
+        render_template_to_file(
+            &mut handlebars,
+            "html",
+            "index.html.handlebars",
+            &page_filename(locale),
+            &page,
+        );
+    }
 
     render_template_to_file(
         &mut handlebars,
         "html",
         "sitemap.xml.handlebars",
         "sitemap.xml",
-        &user_info,
+        &Sitemap {
+            last_modified: Utc::now().to_rfc3339(),
+            alternates: &alternates,
+        },
     );
 
     println!("cargo:rerun-if-changed=build.rs");
@@ -70,19 +199,228 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
-fn render_template_to_file(
+fn page_filename(locale: Option<&str>) -> String {
+    match locale {
+        Some(locale) => format!("index.{}.html", locale),
+        None => "index.html".to_string(),
+    }
+}
+
+/// Every index page, the one at the root of the site as x-default.
+fn alternates(homepage: &str) -> Vec<Alternate> {
+    let base = homepage.trim_end_matches('/');
+    let locales = i18n::LOCALES.iter().map(|locale| Some(*locale));
+    std::iter::once(None)
+        .chain(locales)
+        .map(|locale| Alternate {
+            hreflang: locale.unwrap_or("x-default").to_string(),
+            href: format!("{}/{}", base, page_filename(locale)),
+        })
+        .collect()
+}
+
+fn parse_date(date: &Option<String>) -> Option<NaiveDate> {
+    date.as_ref()
+        .and_then(|date| NaiveDate::parse_from_str(date, DATE_FORMAT).ok())
+}
+
+fn invalid_sites(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// What serde can't check: URLs that don't parse, a homepage the templates can't link to, handles that
+/// can't match a path word and text for locales the site doesn't have.
+fn check_sites(user_info: &UserInfoResponse) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut problem = |value: &str, message: String| {
+        problems.push(Problem {
+            value: value.to_string(),
+            message,
+        })
+    };
+
+    for (field, value) in [
+        ("homepage", &user_info.homepage),
+        ("source", &user_info.source),
+    ] {
+        match Url::parse(value) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {}
+            Ok(_) => problem(value, format!("{} should be an http or https URL", field)),
+            Err(error) => problem(
+                value,
+                format!("{} should be an absolute URL: {}", field, error),
+            ),
+        }
+    }
+
+    check_localised(&user_info.name, "name", "name", &mut problem);
+
+    for site in &user_info.sites {
+        for (field, text) in [("label", &site.label), ("description", &site.description)] {
+            if let Some(text) = text {
+                check_localised(
+                    text,
+                    &format!("{} of {}", field, site.url),
+                    &site.url,
+                    &mut problem,
+                );
+            }
+        }
+
+        if let Err(error) = Url::parse(&site.url) {
+            problem(
+                &site.url,
+                format!("can't parse URL {:?}: {}", site.url, error),
+            );
+        }
+
+        for date in [&site.valid_from, &site.valid_until]
+            .iter()
+            .copied()
+            .flatten()
+        {
+            if let Err(error) = NaiveDate::parse_from_str(date, DATE_FORMAT) {
+                problem(
+                    date,
+                    format!("{:?} should be a YYYY-MM-DD date: {}", date, error),
+                );
+            }
+        }
+
+        if let (Some(from), Some(until)) =
+            (parse_date(&site.valid_from), parse_date(&site.valid_until))
+        {
+            if until < from {
+                problem(
+                    site.valid_until.as_deref().unwrap_or_default(),
+                    format!(
+                        "{} is valid until {}, before it's valid from {}",
+                        site.url, until, from
+                    ),
+                );
+            }
+        }
+
+        for tag in &site.tags {
+            if tag.trim().is_empty() {
+                problem(tag, format!("{} has an empty tag", site.url));
+            }
+        }
+
+        if let Some(icon) = &site.icon {
+            // Relative to the homepage, which is where the page is published
+            if let Err(error) = Url::parse(&user_info.homepage).and_then(|base| base.join(icon)) {
+                problem(
+                    icon,
+                    format!("can't parse icon address {:?}: {}", icon, error),
+                );
+            }
+        }
+
+        if let Some(handle) = &site.handle {
+            if let Err(error) = check_handle(handle) {
+                problem(handle, format!("handle {:?} {}", handle, error));
+            }
+        }
+    }
+
+    let mut seen: Vec<String> = vec![];
+    for alias in &user_info.aliases {
+        if let Err(error) = check_handle(alias) {
+            problem(alias, format!("alias {:?} {}", alias, error));
+        }
+
+        let key = alias.trim_start_matches('@').to_lowercase();
+        if seen.contains(&key) {
+            problem(alias, format!("alias {:?} is repeated", alias));
+        }
+        seen.push(key);
+    }
+
+    problems
+}
+
+/// Text written for each locale needs at least one, and only ones the site has a catalogue for. `near` is
+/// where to point at when there's no text.
+fn check_localised(
+    text: &Localised,
+    what: &str,
+    near: &str,
+    problem: &mut impl FnMut(&str, String),
+) {
+    if text.get(i18n::DEFAULT_LOCALE).is_none() {
+        problem(near, format!("{} has no text", what));
+    }
+
+    for locale in text.locales() {
+        if i18n::supported(locale).is_none() {
+            problem(
+                locale,
+                format!(
+                    "{} is written for {:?}, but the site is only in {}",
+                    what,
+                    locale,
+                    i18n::LOCALES.join(", ")
+                ),
+            );
+        }
+    }
+}
+
+/// Aliases and handles are matched against words in URL paths, so each has to look like one.
+fn check_handle(handle: &str) -> Result<(), &'static str> {
+    let handle = handle.trim_start_matches('@');
+    if handle.is_empty() {
+        Err("is empty")
+    } else if handle.chars().any(|c| c.is_whitespace() || c == '/') {
+        Err("can't have spaces or slashes")
+    } else {
+        Ok(())
+    }
+}
+
+/// Line and column, from 1, of the first place the string value is written in the JSON text. The start of
+/// the file if it can't be found, e.g. because it's written with escapes.
+fn locate(json: &str, value: &str) -> (usize, usize) {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    let offset = json.find(&quoted).unwrap_or(0);
+    let before = &json[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |newline| newline + 1)..]
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+/// The site merges links going to the same place, but the file is probably not what the author meant.
+fn warn_about_duplicates(user_info: &UserInfoResponse) {
+    let urls: Vec<&str> = user_info
+        .sites
+        .iter()
+        .map(|site| site.url.as_str())
+        .collect();
+    for (duplicate, first) in canonical::duplicates(&urls) {
+        println!(
+            "cargo:warning={} goes to the same place as {}, only the first will be shown",
+            urls[duplicate], urls[first]
+        );
+    }
+}
+
+fn render_template_to_file<T: Serialize>(
     handlebars: &mut Handlebars,
 
     directory: &str,
     template_filename: &str,
     output_filename: &str,
-    user_info: &UserInfoResponse,
+    data: &T,
 ) {
     let source = read_file(&[directory, template_filename]).unwrap();
     assert!(handlebars
         .register_template_string(template_filename, source)
         .is_ok());
-    let final_text = handlebars.render(template_filename, &user_info).unwrap();
+    let final_text = handlebars.render(template_filename, data).unwrap();
     let dest_path = get_path(&[STATIC_DIR, output_filename]);
     fs::write(&dest_path, final_text).unwrap();
 }
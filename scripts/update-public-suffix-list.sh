@@ -0,0 +1,35 @@
+#!/usr/bin/env bash
+
+# Refreshes src/urltools/public_suffix_list.dat from publicsuffix.org, or from a copy of the list given as the
+# first argument. Comments and blank lines are dropped to keep the wasm small, the section markers stay.
+
+set -e
+set -o pipefail
+
+THIS_SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
+cd "$THIS_SCRIPT_DIR"/..
+
+SOURCE_URL=https://publicsuffix.org/list/public_suffix_list.dat
+DESTINATION=src/urltools/public_suffix_list.dat
+
+LIST=$(mktemp)
+trap 'rm -f "$LIST"' EXIT
+
+if [ -n "$1" ]; then
+    cp "$1" "$LIST"
+else
+    curl --fail --silent --show-error "$SOURCE_URL" >"$LIST"
+fi
+
+# Newer lists say which version and commit they are, older ones only have the date we took them
+version=$(sed -n 's|^// VERSION: *||p' "$LIST")
+commit=$(sed -n 's|^// COMMIT: *||p' "$LIST")
+
+{
+    head -n 3 "$LIST"
+    echo "//"
+    echo "// Copied from $SOURCE_URL with comments and blank lines removed."
+    echo "// Version: ${version:-taken on $(date -u +%Y-%m-%d)}${commit:+, commit $commit}"
+    echo "// Update with scripts/update-public-suffix-list.sh"
+    grep -E '^[^/]|^// ===(BEGIN|END) (ICANN|PRIVATE) DOMAINS===$' "$LIST"
+} >"$DESTINATION"
@@ -0,0 +1,485 @@
+//! Previews the labels the site will show for a list of URLs, without building the wasm.
+//!
+//! ```text
+//! abbreviate [--names "John Doe"] [--max 30] [--lang en] [--json] [--sites sites.json] [URL...]
+//! ```
+//!
+//! URLs come from the arguments, from `--sites` or, if there are neither, from stdin, one per line. With
+//! `--sites` the names default to the name in the file, along with its aliases and each site's handle, and
+//! the sites are prepared as the site does: hidden, expired and not yet valid ones are left out, the rest
+//! are sorted and duplicates merged. Text written for each locale is shown as the site would in `--lang`. Exits
+//! with 1 if any labels collide, go over the budget or can't be worked out, and with 2 on usage errors.
+//! Account labels are shown whole, so they never count as over the budget.
+
+use personal_site::i18n::{self, Localised};
+use personal_site::urltools::{Abbreviator, Link, Measure};
+use personal_site::{label_names, prepare_sites, site_abbreviator, Site, LABEL_MAX_LENGTH};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str =
+    "usage: abbreviate [--names \"John Doe\"] [--max 30] [--lang en] [--json] [--sites sites.json] [URL...]";
+const ARROW: &str = " → ";
+
+#[derive(Deserialize)]
+struct SitesFile {
+    name: Localised,
+    #[serde(default, alias = "handles")]
+    aliases: Vec<String>,
+    sites: Vec<Site>,
+}
+
+struct Args {
+    names: Option<String>,
+    max_length: usize,
+    locale: &'static str,
+    json: bool,
+    help: bool,
+    sites_path: Option<String>,
+    urls: Vec<String>,
+}
+
+struct Entry {
+    url: String,
+    label: Result<String, String>,
+    collides: bool,
+    over_budget: bool,
+}
+
+impl Entry {
+    fn is_ok(&self) -> bool {
+        self.label.is_ok() && !self.collides && !self.over_budget
+    }
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => fail(&message),
+    };
+
+    if args.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let (sites, file_name, aliases) = match &args.sites_path {
+        Some(path) => {
+            let file = read_sites(path).unwrap_or_else(|message| fail(&message));
+            let sites = prepare_sites(file.sites, &today());
+            let name = file.name.get(args.locale).map(String::from);
+            (sites, name, file.aliases)
+        }
+        None if args.urls.is_empty() => (read_stdin(), None, vec![]),
+        None => (
+            args.urls.iter().map(|url| site(url)).collect(),
+            None,
+            vec![],
+        ),
+    };
+
+    let names = args.names.or(file_name).unwrap_or_default();
+
+    let entries = label_sites(
+        &site_abbreviator(),
+        &sites,
+        &names,
+        &aliases,
+        args.max_length,
+        args.locale,
+    );
+
+    if args.json {
+        print_json(&entries);
+    } else {
+        print_table(&entries);
+    }
+
+    if !entries.iter().all(Entry::is_ok) {
+        process::exit(1);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mut result = Args {
+        names: None,
+        max_length: LABEL_MAX_LENGTH,
+        locale: i18n::DEFAULT_LOCALE,
+        json: false,
+        help: false,
+        sites_path: None,
+        urls: vec![],
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+
+        match arg.as_str() {
+            "--names" => result.names = Some(value("--names")?),
+            "--max" => {
+                let max = value("--max")?;
+                result.max_length = max
+                    .parse()
+                    .map_err(|_| format!("--max should be a number, not {:?}", max))?;
+            }
+            "--lang" => {
+                let lang = value("--lang")?;
+                result.locale = i18n::supported(&lang).ok_or(format!(
+                    "the site isn't in {:?}, only in {}",
+                    lang,
+                    i18n::LOCALES.join(", ")
+                ))?;
+            }
+            "--json" => result.json = true,
+            "--sites" => result.sites_path = Some(value("--sites")?),
+            "--help" | "-h" => result.help = true,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            url => result.urls.push(url.to_string()),
+        }
+    }
+
+    if result.sites_path.is_some() && !result.urls.is_empty() {
+        return Err("use either --sites or URLs, not both".to_string());
+    }
+
+    Ok(result)
+}
+
+fn read_sites(path: &str) -> Result<SitesFile, String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("can't read {}: {}", path, error))?;
+    serde_json::from_str(&text).map_err(|error| format!("can't parse {}: {}", path, error))
+}
+
+fn read_stdin() -> Vec<Site> {
+    io::stdin()
+        .lock()
+        .lines()
+        .filter_map(Result::ok)
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .map(|url| site(&url))
+        .collect()
+}
+
+fn site(url: &str) -> Site {
+    Site {
+        url: url.to_string(),
+        ..Site::default()
+    }
+}
+
+/// The current day in UTC, as YYYY-MM-DD like the site uses.
+fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    date((seconds / 86_400) as i64)
+}
+
+/// The day that's the given number of days after 1970-01-01, as YYYY-MM-DD. Counts in 400 year eras, which
+/// always have the same number of days, and years starting in March, so the leap day comes last.
+fn date(days: i64) -> String {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn label_sites(
+    abbreviator: &Abbreviator,
+    sites: &[Site],
+    name: &str,
+    aliases: &[String],
+    max_length: usize,
+    locale: &str,
+) -> Vec<Entry> {
+    let measure = abbreviator.options().measure;
+    let names: Vec<Vec<&str>> = sites
+        .iter()
+        .map(|site| label_names(name, aliases, site.handle.as_deref()))
+        .collect();
+    let links: Vec<Link> = sites
+        .iter()
+        .zip(&names)
+        .map(|(site, names)| Link {
+            url: &site.url,
+            names,
+            fediverse: site.fediverse,
+        })
+        .collect();
+    let labels = abbreviator.label_list(&links, Some(max_length));
+
+    let mut entries: Vec<Entry> = sites
+        .iter()
+        .zip(labels)
+        .map(|(site, label)| {
+            // Account labels are shown whole, they aren't meant to fit
+            let budgeted =
+                site.label.is_some() || !abbreviator.is_account(&site.url, site.fediverse);
+            // The site shows its own label as it is, even if it doesn't fit
+            let label = match site.label.as_ref().and_then(|label| label.get(locale)) {
+                Some(custom) => Ok(custom.to_string()),
+                None => label
+                    .map(|abbreviation| abbreviation.text)
+                    .map_err(|error| error.to_string()),
+            };
+            let over_budget = budgeted
+                && label
+                    .as_ref()
+                    .map_or(false, |label| measure.length_of(label) > max_length);
+
+            Entry {
+                url: site.url.clone(),
+                label,
+                collides: false,
+                over_budget,
+            }
+        })
+        .collect();
+
+    mark_collisions(&mut entries);
+    entries
+}
+
+/// Two different URLs showing the same label can't be told apart, even after the abbreviator has brought back
+/// what it could. Labels that only differ in case are easy to mistake for each other, so case doesn't count.
+fn mark_collisions(entries: &mut [Entry]) {
+    let mut urls_by_label: HashMap<String, Vec<String>> = HashMap::new();
+    for entry in entries.iter() {
+        if let Ok(label) = &entry.label {
+            let urls = urls_by_label.entry(label.to_lowercase()).or_default();
+            if !urls.contains(&entry.url) {
+                urls.push(entry.url.clone());
+            }
+        }
+    }
+
+    for entry in entries.iter_mut() {
+        if let Ok(label) = &entry.label {
+            entry.collides = urls_by_label
+                .get(&label.to_lowercase())
+                .map_or(false, |urls| urls.len() > 1);
+        }
+    }
+}
+
+fn print_table(entries: &[Entry]) {
+    let measure = Measure::DisplayWidth;
+    let url_width = entries
+        .iter()
+        .map(|entry| measure.length_of(&entry.url))
+        .max()
+        .unwrap_or(0);
+
+    for entry in entries {
+        let padding = " ".repeat(url_width - measure.length_of(&entry.url));
+        let label = match &entry.label {
+            Ok(label) => label.clone(),
+            Err(error) => format!("error: {}", error),
+        };
+
+        let mut problems = vec![];
+        if entry.collides {
+            problems.push("collides");
+        }
+        if entry.over_budget {
+            problems.push("too long");
+        }
+        let problems = if problems.is_empty() {
+            "".to_string()
+        } else {
+            format!("  [{}]", problems.join(", "))
+        };
+
+        println!("{}{}{}{}{}", entry.url, padding, ARROW, label, problems);
+    }
+}
+
+fn print_json(entries: &[Entry]) {
+    let entries: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|entry| match &entry.label {
+            Ok(label) => json!({
+                "url": entry.url,
+                "label": label,
+                "collides": entry.collides,
+                "over_budget": entry.over_budget,
+            }),
+            Err(error) => json!({
+                "url": entry.url,
+                "error": error,
+            }),
+        })
+        .collect();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&entries).expect("JSON values always serialise")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn sites(urls: &[&str]) -> Vec<Site> {
+        urls.iter().map(|url| site(url)).collect()
+    }
+
+    #[test]
+    fn should_parse_options_and_urls() {
+        let parsed = args(&[
+            "--max",
+            "20",
+            "--names",
+            "John Doe",
+            "--lang",
+            "es-AR",
+            "--json",
+            "https://a.com",
+        ])
+        .unwrap();
+        assert_eq!(parsed.max_length, 20);
+        assert_eq!(parsed.names.as_deref(), Some("John Doe"));
+        assert_eq!(parsed.locale, "es");
+        assert!(parsed.json);
+        assert_eq!(parsed.urls, vec!["https://a.com"]);
+        assert!(!parsed.help);
+        assert!(args(&["-h"]).unwrap().help);
+    }
+
+    #[test]
+    fn should_reject_bad_arguments() {
+        assert!(args(&["--max", "lots"]).is_err());
+        assert!(args(&["--max"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+        assert!(args(&["--lang", "fr"]).is_err());
+        assert!(args(&["--sites", "sites.json", "https://a.com"]).is_err());
+    }
+
+    #[test]
+    fn should_write_dates_like_the_site() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_744), "2026-10-18");
+    }
+
+    #[test]
+    fn should_flag_colliding_labels() {
+        let entries = label_sites(
+            &site_abbreviator(),
+            &sites(&[
+                "https://example.com/a/very/long/path/here",
+                "https://example.com/another/long/path/there",
+                "https://github.com/diegov",
+            ]),
+            "",
+            &[],
+            12,
+            "en",
+        );
+
+        assert!(entries[0].collides);
+        assert!(entries[1].collides);
+        assert!(!entries[2].collides);
+    }
+
+    #[test]
+    fn should_not_flag_the_same_url_twice() {
+        let entries = label_sites(
+            &site_abbreviator(),
+            &sites(&["https://example.com", "https://example.com"]),
+            "",
+            &[],
+            30,
+            "en",
+        );
+
+        assert!(entries.iter().all(Entry::is_ok));
+    }
+
+    #[test]
+    fn should_keep_aliases_and_handles() {
+        let url = "https://example.com/work/diegov/projects";
+        let mut with_handle = site(url);
+        with_handle.handle = Some("@diegov".to_string());
+
+        let labels = |sites: &[Site], aliases: &[String]| {
+            label_sites(&site_abbreviator(), sites, "Person", aliases, 22, "en")
+                .into_iter()
+                .map(|entry| entry.label.unwrap())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(labels(&[site(url)], &[]), vec!["example.com/work"]);
+        assert_eq!(
+            labels(&[site(url)], &["diegov".to_string()]),
+            vec!["example.com/.../diegov"]
+        );
+        assert_eq!(
+            labels(&[with_handle], &["someone".to_string()]),
+            vec!["example.com/.../diegov"]
+        );
+    }
+
+    #[test]
+    fn should_flag_labels_over_budget() {
+        let entries = label_sites(
+            &site_abbreviator(),
+            &sites(&[
+                "https://github.com/diegov",
+                "https://a.com",
+                "https://averylonghostname.com",
+            ]),
+            "",
+            &[],
+            10,
+            "en",
+        );
+
+        assert_eq!(entries[0].label.as_deref(), Ok("GitHub · diegov"));
+        assert!(!entries[0].over_budget);
+        assert!(!entries[1].over_budget);
+        assert!(entries[2].over_budget);
+    }
+
+    #[test]
+    fn should_show_custom_labels() {
+        let mut custom = site("mailto:jdoe@example.com");
+        custom.label =
+            Some(serde_json::from_str(r#"{ "en": "Email me", "es": "Escribime" }"#).unwrap());
+        let custom = [custom];
+
+        let entries = label_sites(&site_abbreviator(), &custom, "", &[], 30, "en");
+        assert_eq!(entries[0].label.as_deref(), Ok("Email me"));
+        let entries = label_sites(&site_abbreviator(), &custom, "", &[], 30, "es");
+        assert_eq!(entries[0].label.as_deref(), Ok("Escribime"));
+    }
+}
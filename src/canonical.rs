@@ -0,0 +1,154 @@
+//! Tells when two URLs in the site list go to the same place. Kept free of the rest of the crate, build.rs
+//! includes it to check sites.json.
+
+use url::Url;
+
+// Query parameters that only say where the visitor came from
+const TRACKING_PARAMETERS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "igshid", "mc_cid", "mc_eid", "ref_src", "_hsenc",
+    "_hsmi",
+];
+const TRACKING_PREFIXES: &[&str] = &["utm_"];
+
+/// The same URL written the way we compare them: https instead of http, without www, default ports,
+/// trailing slashes or tracking parameters. Hosts are lowercased by the parser already.
+pub fn canonical_url(url_string: &str) -> Result<Url, url::ParseError> {
+    let mut url = Url::parse(url_string.trim())?;
+
+    if url.scheme() == "http" {
+        // Only fails for schemes that can't have a host, and http always has one
+        let _ = url.set_scheme("https");
+    }
+
+    if url.port() == Some(443) && url.scheme() == "https" {
+        let _ = url.set_port(None);
+    }
+
+    let without_www = url
+        .host_str()
+        .and_then(|host| host.strip_prefix("www."))
+        .filter(|rest| !rest.is_empty())
+        .map(str::to_string);
+    if let Some(host) = without_www {
+        url.set_host(Some(&host))?;
+    }
+
+    if url.path().len() > 1 && url.path().ends_with('/') {
+        let path = url.path().trim_end_matches('/').to_string();
+        url.set_path(&path);
+    }
+
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| !is_tracking(key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if query.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(query);
+    }
+
+    if url.fragment() == Some("") {
+        url.set_fragment(None);
+    }
+
+    Ok(url)
+}
+
+fn is_tracking(key: &str) -> bool {
+    let key = key.to_lowercase();
+    TRACKING_PARAMETERS.contains(&key.as_str())
+        || TRACKING_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+}
+
+/// For each URL that goes to the same place as an earlier one, its index and the index of the first one.
+/// URLs that don't parse are compared as they are.
+pub fn duplicates<S: AsRef<str>>(urls: &[S]) -> Vec<(usize, usize)> {
+    let keys: Vec<String> = urls
+        .iter()
+        .map(|url| {
+            let url = url.as_ref();
+            canonical_url(url).map_or_else(|_| url.to_string(), String::from)
+        })
+        .collect();
+
+    keys.iter()
+        .enumerate()
+        .filter_map(|(index, key)| {
+            keys[..index]
+                .iter()
+                .position(|earlier| earlier == key)
+                .map(|first| (index, first))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(url: &str) -> String {
+        canonical_url(url).unwrap().to_string()
+    }
+
+    #[test]
+    fn should_normalise_scheme_host_and_port() {
+        assert_eq!(
+            canonical("http://WWW.Example.com:80/u"),
+            "https://example.com/u"
+        );
+        assert_eq!(
+            canonical("https://example.com:443/u"),
+            "https://example.com/u"
+        );
+        assert_eq!(
+            canonical("https://example.com:8443/u"),
+            "https://example.com:8443/u"
+        );
+        assert_eq!(canonical("https://www./u"), "https://www./u");
+    }
+
+    #[test]
+    fn should_remove_trailing_slashes_but_keep_path_case() {
+        assert_eq!(
+            canonical("https://example.com/User//"),
+            "https://example.com/User"
+        );
+        assert_eq!(canonical("https://example.com/"), "https://example.com/");
+    }
+
+    #[test]
+    fn should_remove_tracking_parameters() {
+        assert_eq!(
+            canonical("https://example.com/watch?utm_source=x&v=abc&fbclid=1#"),
+            "https://example.com/watch?v=abc"
+        );
+        assert_eq!(
+            canonical("https://example.com/u?UTM_Medium=social"),
+            "https://example.com/u"
+        );
+    }
+
+    #[test]
+    fn should_leave_other_schemes() {
+        assert_eq!(canonical("mailto:me@example.com"), "mailto:me@example.com");
+    }
+
+    #[test]
+    fn should_find_duplicates() {
+        assert_eq!(
+            duplicates(&[
+                "http://x.com/u/",
+                "https://www.x.com/u",
+                "https://x.com/v",
+                "https://x.com/u?utm_source=feed",
+                "not a url",
+                "not a url",
+            ]),
+            vec![(1, 0), (3, 0), (5, 4)]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! The text the site shows in each language it has, and how it picks one. Kept free of the rest of the crate
+//! and of the browser, build.rs includes it to write a page for each locale.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Shown when nothing else matches, and in the page at the root of the site.
+pub const DEFAULT_LOCALE: &str = "en";
+/// The locales there's a catalogue for, as BCP 47 language tags.
+pub const LOCALES: &[&str] = &["en", "es"];
+// Query parameter that picks the locale over the browser's preferences, as in "/?lang=es"
+const OVERRIDE_PARAMETER: &str = "lang";
+
+/// Every piece of text the site shows that doesn't come from sites.json.
+pub struct Messages {
+    /// The page title, `{name}` is replaced with the person's name.
+    pub title: &'static str,
+    pub loading: &'static str,
+    pub no_wasm: &'static str,
+    pub no_wasm_title: &'static str,
+    pub nothing_left: &'static str,
+    pub reset: &'static str,
+    pub battery_warning: &'static str,
+    pub ugly_but_functional: &'static str,
+    pub dont_care: &'static str,
+    /// Followed by a link to Firefox.
+    pub best_viewed_in: &'static str,
+    pub source: &'static str,
+    pub suspicious_address: &'static str,
+}
+
+const EN: Messages = Messages {
+    title: "{name}'s Personal Site",
+    loading: "Loading...",
+    no_wasm: "If this page doesn't update, your browser might not support webassembly",
+    no_wasm_title: "No wasm?",
+    nothing_left: "There's nothing left!",
+    reset: "Reset",
+    battery_warning: "(This will probably drain your battery, don't leave it running too long...)",
+    ugly_but_functional: "Ugly but functional",
+    dont_care: "Don't care",
+    best_viewed_in: "Best viewed in ",
+    source: "Source for this site",
+    suspicious_address: "This address could be imitating a different site",
+};
+
+const ES: Messages = Messages {
+    title: "Sitio personal de {name}",
+    loading: "Cargando...",
+    no_wasm: "Si esta página no se actualiza, puede que tu navegador no soporte webassembly",
+    no_wasm_title: "¿Sin wasm?",
+    nothing_left: "¡No queda nada!",
+    reset: "Empezar de nuevo",
+    battery_warning:
+        "(Esto probablemente te gaste la batería, no lo dejes corriendo mucho tiempo...)",
+    ugly_but_functional: "Feo pero funcional",
+    dont_care: "No me importa",
+    best_viewed_in: "Se ve mejor en ",
+    source: "Código fuente de este sitio",
+    suspicious_address: "Esta dirección podría estar imitando otro sitio",
+};
+
+/// The catalogue for one of `LOCALES`, the default one for anything else.
+pub fn messages(locale: &str) -> &'static Messages {
+    match locale {
+        "es" => &ES,
+        _ => &EN,
+    }
+}
+
+/// The page title in the locale.
+pub fn title(locale: &str, name: &str) -> String {
+    messages(locale).title.replace("{name}", name)
+}
+
+/// The locale the site has for a language tag: the same tag, or one for the same language, so "es-AR" gets
+/// "es". Case doesn't count.
+pub fn supported(tag: &str) -> Option<&'static str> {
+    let tag = tag.trim().to_lowercase();
+    let language = tag.split(|c| c == '-' || c == '_').next().unwrap_or("");
+    LOCALES
+        .iter()
+        .find(|locale| tag == **locale)
+        .or_else(|| LOCALES.iter().find(|locale| language == **locale))
+        .copied()
+}
+
+/// The first of the preferred languages, as in `navigator.languages`, that the site has.
+pub fn negotiate<S: AsRef<str>>(preferred: &[S]) -> &'static str {
+    preferred
+        .iter()
+        .find_map(|tag| supported(tag.as_ref()))
+        .unwrap_or(DEFAULT_LOCALE)
+}
+
+/// The locale asked for in the query string of the page's URL, if the site has it.
+pub fn locale_override(query: &str) -> Option<&'static str> {
+    url::form_urlencoded::parse(query.trim_start_matches('?').as_bytes())
+        .find(|(key, _)| key == OVERRIDE_PARAMETER)
+        .and_then(|(_, value)| supported(&value))
+}
+
+/// Picks the locale to show: the one in the URL, then the one the page was written in, if it's one of the
+/// per-locale pages, then the browser's preferences.
+pub fn choose<S: AsRef<str>>(query: &str, page: Option<&str>, preferred: &[S]) -> &'static str {
+    locale_override(query)
+        .or_else(|| page.and_then(supported))
+        .unwrap_or_else(|| negotiate(preferred))
+}
+
+/// Text in sites.json, either the same in every locale or written for each of them.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Localised {
+    Text(String),
+    PerLocale(BTreeMap<String, String>),
+}
+
+impl Localised {
+    /// The text for the locale, falling back to the same language, then to the default locale, then to any
+    /// text there is. None only when there's no text at all.
+    pub fn get(&self, locale: &str) -> Option<&str> {
+        match self {
+            Localised::Text(text) => Some(text),
+            Localised::PerLocale(texts) => {
+                let language = locale.split('-').next().unwrap_or(locale);
+                texts
+                    .iter()
+                    .find(|(tag, _)| tag.eq_ignore_ascii_case(locale))
+                    .or_else(|| {
+                        texts
+                            .iter()
+                            .find(|(tag, _)| supported(tag) == Some(language))
+                    })
+                    .or_else(|| texts.iter().find(|(tag, _)| *tag == DEFAULT_LOCALE))
+                    .or_else(|| texts.iter().next())
+                    .map(|(_, text)| text.as_str())
+            }
+        }
+    }
+
+    /// The locales the text is written for, none when it's the same in all of them.
+    pub fn locales(&self) -> Vec<&str> {
+        match self {
+            Localised::Text(_) => vec![],
+            Localised::PerLocale(texts) => texts.keys().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_locales_by_language() {
+        assert_eq!(supported("es"), Some("es"));
+        assert_eq!(supported("ES-ar"), Some("es"));
+        assert_eq!(supported("en_GB"), Some("en"));
+        assert_eq!(supported("fr"), None);
+    }
+
+    #[test]
+    fn should_pick_the_first_preferred_locale_the_site_has() {
+        assert_eq!(negotiate(&["fr-FR", "es-AR", "en"]), "es");
+        assert_eq!(negotiate(&["fr"]), DEFAULT_LOCALE);
+        assert_eq!(negotiate::<&str>(&[]), DEFAULT_LOCALE);
+    }
+
+    #[test]
+    fn url_should_override_page_and_browser() {
+        assert_eq!(choose("?lang=es", Some("en"), &["en"]), "es");
+        assert_eq!(choose("?theme=dark&lang=es-MX", None, &["en"]), "es");
+        assert_eq!(choose("?lang=fr", Some("es"), &["en"]), "es");
+        assert_eq!(choose("", None, &["es"]), "es");
+        assert_eq!(choose("?lang=fr", None, &["fr"]), DEFAULT_LOCALE);
+    }
+
+    #[test]
+    fn should_fill_in_the_title() {
+        assert_eq!(title("en", "Jane"), "Jane's Personal Site");
+        assert_eq!(title("es", "Jane"), "Sitio personal de Jane");
+    }
+
+    #[test]
+    fn should_fall_back_to_other_locales() {
+        let text: Localised =
+            serde_json::from_str(r#"{ "es-AR": "Escribime", "en": "Email me" }"#).unwrap();
+        assert_eq!(text.get("es"), Some("Escribime"));
+        assert_eq!(text.get("en"), Some("Email me"));
+
+        let spanish: Localised = serde_json::from_str(r#"{ "es": "Escribime" }"#).unwrap();
+        assert_eq!(spanish.get("en"), Some("Escribime"));
+
+        let plain: Localised = serde_json::from_str(r#""Email me""#).unwrap();
+        assert_eq!(plain.get("es"), Some("Email me"));
+        assert!(plain.locales().is_empty());
+
+        let empty: Localised = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty.get("en"), None);
+    }
+}
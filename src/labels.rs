@@ -0,0 +1,120 @@
+use crate::urltools::{AbbreviateError, Abbreviation, Abbreviator, Link};
+use std::collections::HashMap;
+
+type Label = Result<Abbreviation, AbbreviateError>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct LabelKey {
+    url: String,
+    names: Vec<String>,
+    fediverse: Option<bool>,
+    max_length: usize,
+}
+
+impl LabelKey {
+    fn new(link: &Link, max_length: usize) -> Self {
+        LabelKey {
+            url: link.url.to_string(),
+            names: link.names.iter().map(|name| name.to_string()).collect(),
+            fediverse: link.fediverse,
+            max_length,
+        }
+    }
+}
+
+/// The labels of the last list of links, so loading the same sites again doesn't abbreviate them again.
+#[derive(Default)]
+pub struct LabelCache {
+    labels: HashMap<LabelKey, Label>,
+}
+
+impl LabelCache {
+    /// Labels the links together, as `Abbreviator::label_list` does. A label can depend on the rest of the
+    /// list, so a single link that isn't cached relabels all of them.
+    pub fn labels(
+        &mut self,
+        abbreviator: &Abbreviator,
+        links: &[Link],
+        max_length: usize,
+    ) -> Vec<Label> {
+        let keys: Vec<LabelKey> = links
+            .iter()
+            .map(|link| LabelKey::new(link, max_length))
+            .collect();
+
+        let cached: Option<Vec<Label>> = keys
+            .iter()
+            .map(|key| self.labels.get(key).cloned())
+            .collect();
+        if let Some(cached) = cached {
+            return cached;
+        }
+
+        let labels = abbreviator.label_list(links, Some(max_length));
+        self.labels = keys.into_iter().zip(labels.iter().cloned()).collect();
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site_abbreviator;
+
+    fn link<'a>(url: &'a str, names: &'a [&'a str]) -> Link<'a> {
+        Link {
+            url,
+            names,
+            fediverse: None,
+        }
+    }
+
+    #[test]
+    fn should_reuse_labels_of_the_same_links() {
+        let mut cache = LabelCache::default();
+        let abbreviator = site_abbreviator();
+        let links = [link("https://example.com/jdoe", &["John", "Doe"])];
+
+        let labels = cache.labels(&abbreviator, &links, 30);
+        assert_eq!(cache.labels(&abbreviator, &links, 30), labels);
+        assert_eq!(cache.labels.len(), 1);
+    }
+
+    #[test]
+    fn should_relabel_when_links_or_budget_change() {
+        let mut cache = LabelCache::default();
+        let abbreviator = site_abbreviator();
+        let url = "https://example.com/projects/alpha/overview";
+        let other = "https://example.com/projects/beta/overview";
+
+        let alone = cache.labels(&abbreviator, &[link(url, &[])], 24);
+        assert_eq!(alone[0].as_ref().unwrap().text, "example.com/projects");
+
+        // Now it collides, so it gets back what tells it apart
+        let together = cache.labels(&abbreviator, &[link(url, &[]), link(other, &[])], 24);
+        assert_eq!(together[0].as_ref().unwrap().text, "example.com/.../alpha");
+
+        let longer = cache.labels(&abbreviator, &[link(url, &[])], 40);
+        assert_eq!(
+            longer[0].as_ref().unwrap().text,
+            "example.com/projects/alpha/overview"
+        );
+        assert_eq!(cache.labels.len(), 1);
+    }
+
+    #[test]
+    fn should_relabel_when_fediverse_changes() {
+        let mut cache = LabelCache::default();
+        let abbreviator = site_abbreviator();
+        let url = "https://example.com/@jdoe";
+        let mut links = [link(url, &[])];
+
+        links[0].fediverse = Some(true);
+        let handle = cache.labels(&abbreviator, &links, 30);
+        assert_eq!(handle[0].as_ref().unwrap().text, "@jdoe@example.com");
+
+        links[0].fediverse = Some(false);
+        let path = cache.labels(&abbreviator, &links, 30);
+        assert_eq!(path[0].as_ref().unwrap().text, "example.com/@jdoe");
+    }
+}
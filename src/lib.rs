@@ -6,6 +6,7 @@ extern crate wee_alloc;
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 use anyhow::Error;
+use i18n::Localised;
 use serde::Deserialize;
 use std::time::Duration;
 use wasm_bindgen::prelude::*;
@@ -16,13 +17,26 @@ use yew::format::{Json, Nothing};
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
 use yew::services::{Task, TimeoutService};
 use yew::{html, Component, ComponentLink, Html, ShouldRender};
+pub mod canonical;
 mod canvas;
 mod debug;
+pub mod i18n;
+mod labels;
 mod time;
-mod urltools;
+pub mod urltools;
 
 const REMOVE_TIMEOUT_MS: u64 = 400;
+// Seconds each item glows for, one after the other, and then before starting over
+const GLOW_ITEM_TIME: f64 = 0.12;
+const GLOW_WAIT_TIME: f64 = 7.0;
 const DEFAULT_WS: &str = " ";
+// Schemes we render as links. Anything else, like geo:, rarely has a handler and is shown as plain text.
+const LINK_SCHEMES: &[&str] = &[
+    "http", "https", "mailto", "tel", "xmpp", "matrix", "irc", "ircs",
+];
+
+/// Longest label we show for a site.
+pub const LABEL_MAX_LENGTH: usize = 30;
 
 pub struct Model {
     link: ComponentLink<Self>,
@@ -32,6 +46,9 @@ pub struct Model {
     counter: u128,
     show_aside: bool,
     cursor: (i32, i32),
+    abbreviator: urltools::Abbreviator,
+    labels: labels::LabelCache,
+    locale: &'static str,
 }
 
 pub enum Msg {
@@ -44,25 +61,66 @@ pub enum Msg {
     MouseMove(MouseEvent),
 }
 
-#[derive(Deserialize)]
+/// A link as it's written in sites.json.
+#[derive(Deserialize, Default)]
 pub struct Site {
-    url: String,
-    me: bool,
+    pub url: String,
+    pub me: bool,
+    /// Whether the link is a fediverse account, shown as a handle. Detected from the URL when missing.
+    #[serde(default)]
+    pub fediverse: Option<bool>,
+    /// The person's handle on this site, used instead of the aliases to pick what the label keeps.
+    #[serde(default)]
+    pub handle: Option<String>,
+    /// Shown instead of the abbreviated URL.
+    #[serde(default)]
+    pub label: Option<Localised>,
+    /// Secondary text shown along with the link.
+    #[serde(default)]
+    pub description: Option<Localised>,
+    /// The link is shown in a section named after its first tag.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Address of a small image shown before the link.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Links are sorted by this, lowest first. Missing counts as 0, ties keep the order in the file.
+    #[serde(default)]
+    pub order: Option<i32>,
+    #[serde(default)]
+    pub hidden: bool,
+    /// First day the link is shown, as YYYY-MM-DD.
+    #[serde(default)]
+    pub valid_from: Option<String>,
+    /// Last day the link is shown, as YYYY-MM-DD.
+    #[serde(default)]
+    pub valid_until: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct UserInfoResponse {
-    name: String,
+    name: Localised,
+    /// Handles and other names the person goes by, which labels keep along with the words in the name.
+    #[serde(default, alias = "handles")]
+    aliases: Vec<String>,
     sites: Vec<Site>,
     source: String,
 }
 
 pub struct UserInfo {
     name: String,
-    sites: Vec<(Site, bool, f64)>,
+    sites: Vec<Item>,
     source: String,
 }
 
+/// A site as it's shown in the list.
+pub struct Item {
+    site: Site,
+    label: urltools::Abbreviation,
+    removed: bool,
+    removed_at: f64,
+}
+
 impl Component for Model {
     type Message = Msg;
     type Properties = ();
@@ -76,6 +134,9 @@ impl Component for Model {
             counter: 0,
             show_aside: true,
             cursor: (0, 0),
+            abbreviator: site_abbreviator(),
+            labels: labels::LabelCache::default(),
+            locale: page_locale(),
         }
     }
 
@@ -94,7 +155,7 @@ impl Component for Model {
             }
             Msg::Cleanup => self.cleanup_items(),
             Msg::UserInfo(info_response) => {
-                let user_info = map_response(info_response);
+                let user_info = self.map_response(info_response);
                 self.update_document(&user_info);
                 self.user_info = Some(user_info);
                 self.show_aside = true;
@@ -102,8 +163,8 @@ impl Component for Model {
             }
             Msg::Remove(i) => {
                 if let Some(info) = self.user_info.as_mut() {
-                    info.sites[i].1 = true;
-                    info.sites[i].2 = time::now();
+                    info.sites[i].removed = true;
+                    info.sites[i].removed_at = time::now();
                 }
 
                 let cleanup = self.link.callback(|_| Msg::Cleanup);
@@ -129,29 +190,33 @@ impl Component for Model {
     }
 
     fn view(&self) -> Html {
+        let messages = i18n::messages(self.locale);
         match &self.user_info {
             Some(data) => {
-                let name_parts: Vec<&str> = data.name.split(' ').collect();
-
                 let name = html! {
-                    <h1>{ &data.name }</h1>
+                    <h1 dir="auto">{ &data.name }</h1>
                 };
 
                 let sites = if !data.sites.is_empty() {
-                    html! {
-                        <ul>
-                        { data.sites.iter().enumerate().map(|site| self.render_item(site, &name_parts)).collect::<Html>() }
-                        </ul>
-                    }
+                    // Where each section starts in the page, counting the items in the sections before it
+                    let mut shown = 0;
+                    sections(&data.sites)
+                        .into_iter()
+                        .map(|(tag, indices)| {
+                            let first = shown;
+                            shown += indices.len();
+                            self.render_section(tag, &indices, first, &data.sites)
+                        })
+                        .collect::<Html>()
                 } else {
                     // TODO: This is terrible. I just want to turn a mouse event captured in the main page into a WebGL uniform in the canvas component,
                     // to do that I have to rerender every piece of virtual DOM for every single mouse move event, and somehow trust that the DOM diff
                     // algorithm makes this clear performance atrocity run well.
                     html! {
                         <>
-                        <p>{ "There's nothing left!" } { DEFAULT_WS } <button onclick=self.link.callback(move |_| Msg::Fetch) >{ "Reset" }</button></p>
+                        <p>{ messages.nothing_left } { DEFAULT_WS } <button onclick=self.link.callback(move |_| Msg::Fetch) >{ messages.reset }</button></p>
                         <canvas::Model cursor=self.cursor />
-                        <p>{ "(This will probably drain your battery, don't leave it running too long...)" } </p>
+                        <p>{ messages.battery_warning } </p>
                         </>
                     }
                 };
@@ -170,17 +235,17 @@ impl Component for Model {
                     { sites }
                     </main>
                     <aside class={aside_class}>
-                    <h4>{ "Ugly but functional" } { DEFAULT_WS }<button onclick=self.link.callback(move |_| Msg::HideAside) >{ "Don't care" }</button> </h4>
+                    <h4>{ messages.ugly_but_functional } { DEFAULT_WS }<button onclick=self.link.callback(move |_| Msg::HideAside) >{ messages.dont_care }</button> </h4>
                     </aside>
                     <footer>
-                    { "Best viewed in " } <a href="https://www.mozilla.org/firefox" >{ "Firefox" }</a>
+                    { messages.best_viewed_in } <a href="https://www.mozilla.org/firefox" >{ "Firefox" }</a>
                     { " — " }
-                    <a href={data.source.clone()}>{ "Source for this site" }</a>
+                    <a href={data.source.clone()}>{ messages.source }</a>
                     </footer>
                     </body>
                 }
             }
-            None => html! { <p>{ "Loading..." }</p> },
+            None => html! { <p>{ messages.loading }</p> },
         }
     }
 
@@ -230,12 +295,18 @@ impl Model {
             .document()
             .expect("no document available");
 
-        doc.set_title(&info.name);
+        if let Some(root) = doc.document_element() {
+            // The page at the root of the site says it's in the default locale, whichever one we picked
+            let _ = root.set_attribute("lang", self.locale);
+        }
 
-        let item_time = 0.12;
-        let wait_time = 7.0;
+        doc.set_title(&info.name);
+        if let Ok(Some(title)) = doc.query_selector("title") {
+            // Harmless if it fails, the title will just show RTL names in the wrong order
+            let _ = title.set_attribute("dir", "auto");
+        }
 
-        let style = self.build_animation_style(element_count, item_time, wait_time);
+        let style = self.build_animation_style(element_count, GLOW_ITEM_TIME, GLOW_WAIT_TIME);
 
         set_style(&doc, &style);
     }
@@ -288,19 +359,6 @@ li a {
 ",
         );
 
-        for i in 0..element_count {
-            style.push_str(
-                "
-            li:not(.removed):nth-child(",
-            );
-            style.push_str(&element_count.to_string());
-            style.push_str("n -");
-            style.push_str(&(element_count - i - 1).to_string());
-            style.push_str(") { animation-delay: ");
-            style.push_str(&(item_time * (i as f64)).to_string());
-            style.push_str("s; }\n");
-        }
-
         style
     }
 
@@ -313,8 +371,8 @@ li a {
                 .iter()
                 .enumerate()
                 .rev()
-                .filter(|(_, (_, removed, time))| {
-                    *removed && (curr_time - time) >= (REMOVE_TIMEOUT_MS as f64)
+                .filter(|(_, item)| {
+                    item.removed && (curr_time - item.removed_at) >= (REMOVE_TIMEOUT_MS as f64)
                 })
                 .map(|(index, _)| index)
                 .collect();
@@ -337,22 +395,142 @@ li a {
         should_render
     }
 
-    fn render_item(
+    fn map_response(&mut self, mut response: UserInfoResponse) -> UserInfo {
+        response.sites = prepare_sites(response.sites, &time::today());
+
+        let name = response.name.get(self.locale).unwrap_or_default();
+        let names: Vec<Vec<&str>> = response
+            .sites
+            .iter()
+            .map(|site| label_names(name, &response.aliases, site.handle.as_deref()))
+            .collect();
+        // Labelled together, so links that would look the same get told apart
+        let links: Vec<urltools::Link> = response
+            .sites
+            .iter()
+            .zip(&names)
+            .map(|(site, names)| urltools::Link {
+                url: &site.url,
+                names,
+                fediverse: site.fediverse,
+            })
+            .collect();
+        let labels = self
+            .labels
+            .labels(&self.abbreviator, &links, LABEL_MAX_LENGTH);
+
+        let sites = response
+            .sites
+            .into_iter()
+            .zip(labels)
+            .map(|(site, label)| {
+                let label = label.unwrap_or_else(|error| {
+                    debug::log(&format!("Can't abbreviate url: {}", error));
+                    // An ugly link is still better than no site at all
+                    urltools::Abbreviation::new(
+                        vec![urltools::Segment::Kept(site.url.clone())],
+                        urltools::HostForm::Ascii,
+                    )
+                });
+
+                Item {
+                    site,
+                    label,
+                    removed: false,
+                    removed_at: -1.0,
+                }
+            })
+            .collect();
+
+        UserInfo {
+            name: name.to_string(),
+            sites,
+            source: response.source,
+        }
+    }
+
+    fn render_section(
         &self,
-        (idx, (site, is_deleted, _)): (usize, &(Site, bool, f64)),
-        name_parts: &[&str],
+        tag: Option<&str>,
+        indices: &[usize],
+        first: usize,
+        items: &[Item],
     ) -> Html {
+        let heading = match tag {
+            Some(tag) => html! { <h2 dir="auto">{ tag }</h2> },
+            None => html! {},
+        };
+
+        html! {
+            <section>
+            { heading }
+            <ul>
+            {
+                indices
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, idx)| self.render_item(*idx, first + offset, &items[*idx]))
+                    .collect::<Html>()
+            }
+            </ul>
+            </section>
+        }
+    }
+
+    /// Renders the item at `idx` in the list, shown at `position` in the page.
+    fn render_item(&self, idx: usize, position: usize, item: &Item) -> Html {
+        let site = &item.site;
         let url_string = &site.url;
-        let title = urltools::abbreviate_max(url_string, name_parts, Some(30))
-            .expect("Can't abbreviate url");
+        let label = &item.label;
+        let messages = i18n::messages(self.locale);
+        let title = match site.label.as_ref().and_then(|label| label.get(self.locale)) {
+            // Written by the person, so it's shown as it is
+            Some(custom) => html! { <span class="custom" dir="auto">{ custom }</span> },
+            // URLs read left to right, RTL words are isolated so they can't drag the slashes around them
+            None => html! {
+                <span dir="ltr">
+                { label.segments.iter().map(render_segment).collect::<Html>() }
+                </span>
+            },
+        };
 
-        let css_class = if *is_deleted { "removed" } else { "" };
+        let icon = match &site.icon {
+            Some(icon) => html! { <img class="icon" src={ icon.clone() } alt="" /> },
+            None => html! {},
+        };
+
+        let description = match site
+            .description
+            .as_ref()
+            .and_then(|description| description.get(self.locale))
+        {
+            Some(description) => {
+                html! { <span class="description" dir="auto">{ description }</span> }
+            }
+            None => html! {},
+        };
+
+        let warning = if label.host_form == urltools::HostForm::Punycode {
+            html! {
+                <span class="suspicious" title={ messages.suspicious_address }>{ "⚠" }{ DEFAULT_WS }</span>
+            }
+        } else {
+            html! {}
+        };
+
+        let css_class = if item.removed { "removed" } else { "" };
+        // Items are split into sections, so the delay goes by the position in all of them rather than nth-child
+        let delay = format!("animation-delay: {}s", GLOW_ITEM_TIME * (position as f64));
 
         let button = html! {
-            <button onclick=self.link.callback(move |_| Msg::Remove(idx)) >{ "Don't care" }</button>
+            <button onclick=self.link.callback(move |_| Msg::Remove(idx)) >{ messages.dont_care }</button>
         };
 
-        let link = if site.me {
+        let link = if !is_clickable(url_string) {
+            html! {
+                <span class="unlinked">{ title }</span>
+            }
+        } else if site.me {
             html! {
                 <a href={ url_string.clone() } rel={ "me" }>{ title }</a>
             }
@@ -363,8 +541,8 @@ li a {
         };
 
         html! {
-            <li class={ css_class }>
-            { link } { DEFAULT_WS } { button }
+            <li class={ css_class } style={ delay }>
+            { warning } { icon } { link } { DEFAULT_WS } { button } { description }
             </li>
         }
     }
@@ -405,15 +583,159 @@ fn set_style(doc: &Document, style: &str) {
     }
 }
 
-fn map_response(response: UserInfoResponse) -> UserInfo {
-    UserInfo {
-        name: response.name,
-        sites: response
-            .sites
+/// The locale to show the site in, see `i18n::choose`. The per-locale pages say which one they are in
+/// `data-locale`, the page at the root of the site doesn't.
+fn page_locale() -> &'static str {
+    let window = web_sys::window().expect("no window available");
+    let query = window.location().search().unwrap_or_default();
+    let page = window
+        .document()
+        .and_then(|doc| doc.document_element())
+        .and_then(|root| root.get_attribute("data-locale"));
+    let preferred: Vec<String> = window
+        .navigator()
+        .languages()
+        .iter()
+        .filter_map(|language| language.as_string())
+        .collect();
+
+    i18n::choose(&query, page.as_deref(), &preferred)
+}
+
+fn render_segment(segment: &urltools::Segment) -> Html {
+    let text = urltools::directed_segments(segment.text())
+        .into_iter()
+        .map(render_directed)
+        .collect::<Html>();
+
+    match segment {
+        urltools::Segment::Host(_) => html! { <span class="host">{ text }</span> },
+        urltools::Segment::Kept(_) => html! { <span class="kept">{ text }</span> },
+        urltools::Segment::NameMatch(_) => html! { <span class="name">{ text }</span> },
+        urltools::Segment::User(_) => html! { <span class="user">{ text }</span> },
+        urltools::Segment::Elided { original, .. } => html! {
+            <span class="elided" title={ original.clone() }>{ text }</span>
+        },
+    }
+}
+
+fn render_directed(segment: urltools::DirectedSegment) -> Html {
+    match segment.direction {
+        urltools::Direction::RightToLeft => html! { <bdi dir="rtl">{ segment.text }</bdi> },
+        _ => html! { { segment.text } },
+    }
+}
+
+/// The abbreviator the site labels links with, also used by the `abbreviate` binary to preview them.
+pub fn site_abbreviator() -> urltools::Abbreviator {
+    urltools::Abbreviator::builder()
+        .strategy(urltools::PathElision)
+        .sub_words(true)
+        .build()
+}
+
+/// The names a site's label should keep: the words in the person's name, and their handle on that site or,
+/// when the site doesn't have one, all their aliases.
+pub fn label_names<'a>(
+    name: &'a str,
+    aliases: &'a [String],
+    handle: Option<&'a str>,
+) -> Vec<&'a str> {
+    let mut names: Vec<&str> = name.split(' ').filter(|part| !part.is_empty()).collect();
+    let handles = match handle {
+        Some(handle) => vec![handle],
+        None => aliases.iter().map(String::as_str).collect(),
+    };
+
+    // "@diegov" is written that way in the fediverse, but the path only has "diegov"
+    names.extend(
+        handles
             .into_iter()
-            .map(|s| (s, false, -1.0))
-            .collect(),
-        source: response.source,
+            .map(|handle| handle.trim_start_matches('@')),
+    );
+    names
+}
+
+/// The sites to list on the given day, as YYYY-MM-DD, in the order they're shown and with duplicates merged.
+/// Labels depend on the whole list, so anything showing them should start from this.
+pub fn prepare_sites(mut sites: Vec<Site>, today: &str) -> Vec<Site> {
+    sites.retain(|site| is_shown(site, today));
+    // Stable, so sites without an order keep the order in the file
+    sites.sort_by_key(|site| site.order.unwrap_or(0));
+    merge_duplicates(sites)
+}
+
+/// Keeps the first of the sites going to the same place, with what the others add to it.
+fn merge_duplicates(sites: Vec<Site>) -> Vec<Site> {
+    let duplicates = canonical::duplicates(&sites.iter().map(|site| &site.url).collect::<Vec<_>>());
+    let mut merged_into: Vec<Option<usize>> = vec![None; sites.len()];
+    for (duplicate, first) in duplicates {
+        merged_into[duplicate] = Some(first);
+    }
+
+    let mut result: Vec<Site> = Vec::with_capacity(sites.len());
+    // Where each kept site ended up in the result
+    let mut positions: Vec<usize> = vec![0; sites.len()];
+
+    for (index, site) in sites.into_iter().enumerate() {
+        match merged_into[index] {
+            Some(first) => {
+                let kept = &mut result[positions[first]];
+                kept.me |= site.me;
+                kept.fediverse = kept.fediverse.or(site.fediverse);
+                kept.handle = kept.handle.take().or(site.handle);
+                kept.label = kept.label.take().or(site.label);
+                kept.description = kept.description.take().or(site.description);
+                kept.icon = kept.icon.take().or(site.icon);
+                for tag in site.tags {
+                    if !kept.tags.contains(&tag) {
+                        kept.tags.push(tag);
+                    }
+                }
+            }
+            None => {
+                positions[index] = result.len();
+                result.push(site);
+            }
+        }
+    }
+
+    result
+}
+
+/// Whether the site should be listed on the given day, written as YYYY-MM-DD so dates compare as text.
+fn is_shown(site: &Site, today: &str) -> bool {
+    !site.hidden
+        && site
+            .valid_from
+            .as_deref()
+            .map_or(true, |from| from <= today)
+        && site
+            .valid_until
+            .as_deref()
+            .map_or(true, |until| today <= until)
+}
+
+/// Groups the items by their first tag, in the order each tag first shows up. Untagged items go in a section
+/// without a tag.
+fn sections(items: &[Item]) -> Vec<(Option<&str>, Vec<usize>)> {
+    let mut result: Vec<(Option<&str>, Vec<usize>)> = vec![];
+    for (index, item) in items.iter().enumerate() {
+        let tag = item.site.tags.first().map(String::as_str);
+        match result.iter_mut().find(|(existing, _)| *existing == tag) {
+            Some((_, indices)) => indices.push(index),
+            None => result.push((tag, vec![index])),
+        }
+    }
+    result
+}
+
+fn is_clickable(url_string: &str) -> bool {
+    match url_string.split_once(':') {
+        Some((scheme, _)) => LINK_SCHEMES
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(scheme)),
+        None => false,
     }
 }
 
@@ -421,3 +743,85 @@ fn map_response(response: UserInfoResponse) -> UserInfo {
 pub fn main() {
     yew::start_app::<Model>();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_merge_sites_going_to_the_same_place() {
+        let sites: Vec<Site> = serde_json::from_str(
+            r#"[
+                { "me": false, "url": "http://x.com/u/" },
+                { "me": true, "url": "https://x.com/v" },
+                { "me": true, "url": "https://www.x.com/u?utm_source=feed", "handle": "u" }
+            ]"#,
+        )
+        .unwrap();
+
+        let merged = merge_duplicates(sites);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].url, "http://x.com/u/");
+        assert!(merged[0].me);
+        assert_eq!(merged[0].handle.as_deref(), Some("u"));
+        assert_eq!(merged[1].url, "https://x.com/v");
+    }
+
+    fn site(json: &str) -> Site {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn should_only_show_current_visible_sites() {
+        let today = "2026-10-18";
+        assert!(is_shown(
+            &site(r#"{ "me": true, "url": "https://x.com" }"#),
+            today
+        ));
+        assert!(!is_shown(
+            &site(r#"{ "me": true, "url": "https://x.com", "hidden": true }"#),
+            today
+        ));
+        assert!(is_shown(
+            &site(
+                r#"{ "me": true, "url": "https://x.com", "valid_from": "2026-10-18", "valid_until": "2026-10-18" }"#
+            ),
+            today
+        ));
+        assert!(!is_shown(
+            &site(r#"{ "me": true, "url": "https://x.com", "valid_until": "2026-10-17" }"#),
+            today
+        ));
+        assert!(!is_shown(
+            &site(r#"{ "me": true, "url": "https://x.com", "valid_from": "2027-01-01" }"#),
+            today
+        ));
+    }
+
+    #[test]
+    fn should_group_sites_by_first_tag() {
+        let items: Vec<Item> = [
+            r#"{ "me": true, "url": "https://a.com", "tags": ["code", "work"] }"#,
+            r#"{ "me": true, "url": "https://b.com" }"#,
+            r#"{ "me": true, "url": "https://c.com", "tags": ["social"] }"#,
+            r#"{ "me": true, "url": "https://d.com", "tags": ["code"] }"#,
+        ]
+        .iter()
+        .map(|json| Item {
+            site: site(json),
+            label: urltools::Abbreviation::new(vec![], urltools::HostForm::Ascii),
+            removed: false,
+            removed_at: -1.0,
+        })
+        .collect();
+
+        assert_eq!(
+            sections(&items),
+            vec![
+                (Some("code"), vec![0, 3]),
+                (None, vec![1]),
+                (Some("social"), vec![2]),
+            ]
+        );
+    }
+}
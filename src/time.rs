@@ -1,6 +1,12 @@
 use crate::JsValue;
 use wasm_bindgen::JsCast;
 
+/// The current day in UTC, as YYYY-MM-DD.
+pub fn today() -> String {
+    let iso = String::from(js_sys::Date::new_0().to_iso_string());
+    iso.chars().take(10).collect()
+}
+
 pub fn now() -> f64 {
     js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
         .expect("failed to get performance from global object")
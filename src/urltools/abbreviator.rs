@@ -0,0 +1,254 @@
+use super::strategy::Strategy;
+use super::{abbreviate_impl, collisions, fediverse, platforms};
+use super::{AbbreviateError, Abbreviation, HostForm, Measure, Normaliser, Options, PathElision};
+use std::rc::Rc;
+use url::Url;
+
+/// Abbreviates URLs with a given strategy and options, build one with `Abbreviator::builder()`.
+pub struct Abbreviator {
+    options: Options,
+    strategy: Box<dyn Strategy>,
+}
+
+pub struct AbbreviatorBuilder {
+    options: Options,
+    strategy: Box<dyn Strategy>,
+}
+
+/// A URL labelled along with others in `Abbreviator::label_list`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Link<'a> {
+    pub url: &'a str,
+    /// The names worth keeping in this link's label, as in `Abbreviator::abbreviate`.
+    pub names: &'a [&'a str],
+    /// Whether the URL is a fediverse account, as in `Abbreviator::label_account`.
+    pub fediverse: Option<bool>,
+}
+
+impl Abbreviator {
+    pub fn builder() -> AbbreviatorBuilder {
+        AbbreviatorBuilder {
+            options: Options::default(),
+            strategy: Box::new(PathElision),
+        }
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Abbreviates the URL if it's longer than `max_length`, or just cleans it up if there's no `max_length`.
+    pub fn abbreviate(
+        &self,
+        url_string: &str,
+        important_names: &[&str],
+        max_length: Option<usize>,
+    ) -> Result<Abbreviation, AbbreviateError> {
+        abbreviate_impl(
+            url_string,
+            important_names,
+            max_length,
+            &self.options,
+            self.strategy.as_ref(),
+        )
+    }
+
+    /// Like `abbreviate`, but URLs pointing to a user in a known platform are labelled
+    /// with the platform name and user instead, e.g. "GitHub · diegov", and fediverse
+    /// profiles with their handle, e.g. "@alice@mastodon.social". These account labels
+    /// are shown whole whatever the `max_length`, a cut handle would point to someone else.
+    pub fn label(
+        &self,
+        url_string: &str,
+        important_names: &[&str],
+        max_length: Option<usize>,
+    ) -> Result<Abbreviation, AbbreviateError> {
+        self.label_account(url_string, important_names, max_length, None)
+    }
+
+    /// Like `label`, for when we know whether the URL is a fediverse account. `Some(true)` shows it as a
+    /// handle even over a known platform's label, `Some(false)` never does, and `None` detects it from the path.
+    pub fn label_account(
+        &self,
+        url_string: &str,
+        important_names: &[&str],
+        max_length: Option<usize>,
+        fediverse: Option<bool>,
+    ) -> Result<Abbreviation, AbbreviateError> {
+        match account_label(url_string, fediverse) {
+            Some(label) => Ok(label),
+            None => self.abbreviate(url_string, important_names, max_length),
+        }
+    }
+
+    /// Whether `label_account` shows the URL as an account, which isn't held to `max_length`.
+    pub fn is_account(&self, url_string: &str, fediverse: Option<bool>) -> bool {
+        account_label(url_string, fediverse).is_some()
+    }
+
+    /// Labels links shown together, each as `label_account` would, except that links whose labels collide
+    /// get back some of the path components elided from them, so they can be told apart. The results are in
+    /// the same order as the links.
+    pub fn label_list(
+        &self,
+        links: &[Link],
+        max_length: Option<usize>,
+    ) -> Vec<Result<Abbreviation, AbbreviateError>> {
+        let mut labels = Vec::with_capacity(links.len());
+        let mut restorable = Vec::with_capacity(links.len());
+
+        for link in links {
+            match account_label(link.url, link.fediverse) {
+                Some(label) => {
+                    labels.push(Ok(label));
+                    restorable.push(false);
+                }
+                None => {
+                    labels.push(self.abbreviate(link.url, link.names, max_length));
+                    restorable.push(true);
+                }
+            }
+        }
+
+        // Without a budget nothing is elided, so there's nothing to bring back
+        if let Some(max_length) = max_length {
+            collisions::disambiguate(links, &mut labels, &restorable, max_length, &self.options);
+        }
+
+        labels
+    }
+}
+
+/// The label for an account in a known platform or the fediverse, None for any other URL.
+fn account_label(url_string: &str, fediverse: Option<bool>) -> Option<Abbreviation> {
+    let url = Url::parse(url_string).ok()?;
+    let handle = |explicit| {
+        fediverse::fediverse_handle(&url, explicit)
+            .map(|(segments, host_form)| Abbreviation::new(segments, host_form))
+    };
+
+    if fediverse == Some(true) {
+        if let Some(handle) = handle(true) {
+            return Some(handle);
+        }
+    }
+
+    // Known platforms go first, YouTube's /@user is not a fediverse profile
+    if let Some(label) = platforms::platform_label(&url) {
+        return Some(Abbreviation::new(label, HostForm::Ascii));
+    }
+
+    if fediverse.is_none() {
+        return handle(false);
+    }
+
+    None
+}
+
+impl Default for Abbreviator {
+    fn default() -> Self {
+        Abbreviator::builder().build()
+    }
+}
+
+impl AbbreviatorBuilder {
+    pub fn strategy<S: Strategy + 'static>(mut self, strategy: S) -> Self {
+        self.strategy = Box::new(strategy);
+        self
+    }
+
+    pub fn measure(mut self, measure: Measure) -> Self {
+        self.options.measure = measure;
+        self
+    }
+
+    /// Replaces the elided text, a trailing slash is added when an elided path component had one.
+    pub fn ellipsis<S: Into<String>>(mut self, ellipsis: S) -> Self {
+        self.options.ellipsis = ellipsis.into();
+        self
+    }
+
+    /// Path components up to this length are kept in preference to longer ones.
+    pub fn short_component_length(mut self, length: usize) -> Self {
+        self.options.short_component_length = length;
+        self
+    }
+
+    /// Minimum score for a path component to count as a name, from 0 to 1. 1 only keeps exact matches.
+    pub fn name_match_threshold(mut self, threshold: f64) -> Self {
+        self.options.name_match_threshold = threshold;
+        self
+    }
+
+    /// How names and path words are normalised before comparing them, `Transliterate` by default.
+    pub fn normaliser<N: Normaliser + 'static>(mut self, normaliser: N) -> Self {
+        self.options.normaliser = Rc::new(normaliser);
+        self
+    }
+
+    /// Splits path words at camelCase and letter/digit boundaries, so "johnDoe42" can match "John Doe".
+    pub fn sub_words(mut self, sub_words: bool) -> Self {
+        self.options.sub_words = sub_words;
+        self
+    }
+
+    pub fn strip_www(mut self, strip_www: bool) -> Self {
+        self.options.strip_www = strip_www;
+        self
+    }
+
+    /// Drops leading subdomains that pick a language or mobile version of the site, e.g. "en.m.wikipedia.org".
+    pub fn strip_subdomain_noise(mut self, strip_subdomain_noise: bool) -> Self {
+        self.options.strip_subdomain_noise = strip_subdomain_noise;
+        self
+    }
+
+    pub fn query_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.query_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn fragment_routes(mut self, fragment_routes: bool) -> Self {
+        self.options.fragment_routes = fragment_routes;
+        self
+    }
+
+    /// Replaces the path words elided before anything else, `DEFAULT_BOILERPLATE` by default. Words later in
+    /// the list are elided first.
+    pub fn boilerplate<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.boilerplate = words.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Uses these boilerplate words for the host and its subdomains instead, e.g. where "c" is a name.
+    pub fn host_boilerplate<H, I, S>(mut self, host: H, words: I) -> Self
+    where
+        H: Into<String>,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let host = host.into().to_lowercase();
+        let words = words.into_iter().map(Into::into).collect();
+
+        self.options
+            .host_boilerplate
+            .retain(|(existing, _)| *existing != host);
+        self.options.host_boilerplate.push((host, words));
+        self
+    }
+
+    pub fn build(self) -> Abbreviator {
+        Abbreviator {
+            options: self.options,
+            strategy: self.strategy,
+        }
+    }
+}
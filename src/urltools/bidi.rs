@@ -0,0 +1,163 @@
+use unicode_bidi::{bidi_class, BidiClass};
+
+/// The direction a piece of the abbreviation is written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+    /// Separators, digits and ellipses, which take the direction of whatever surrounds them.
+    Neutral,
+}
+
+/// A piece of the abbreviation written in a single direction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirectedSegment {
+    pub text: String,
+    pub direction: Direction,
+}
+
+/// Splits the text into words and separators, each with its direction. Adjacent pieces going the same way
+/// are merged, so RTL words separated by spaces or hyphens stay together, while slashes and dots between an
+/// RTL word and anything else are left on their own. Rendering the RTL segments isolated inside an LTR
+/// container keeps the separators where they are in the URL.
+pub fn directed_segments(text: &str) -> Vec<DirectedSegment> {
+    let mut result: Vec<DirectedSegment> = vec![];
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if c.is_alphanumeric() || is_mark(c) {
+            word.push(c);
+            continue;
+        }
+
+        push_word(&mut result, &mut word);
+        push_segment(&mut result, c.to_string(), Direction::Neutral);
+    }
+
+    push_word(&mut result, &mut word);
+    merge_rtl_runs(result)
+}
+
+fn is_mark(c: char) -> bool {
+    bidi_class(c) == BidiClass::NSM
+}
+
+fn push_word(result: &mut Vec<DirectedSegment>, word: &mut String) {
+    if word.is_empty() {
+        return;
+    }
+
+    // The first strong character decides, as with dir="auto"
+    let direction = word
+        .chars()
+        .find_map(|c| match bidi_class(c) {
+            BidiClass::L => Some(Direction::LeftToRight),
+            BidiClass::R | BidiClass::AL => Some(Direction::RightToLeft),
+            _ => None,
+        })
+        .unwrap_or(Direction::Neutral);
+
+    push_segment(result, std::mem::take(word), direction);
+}
+
+fn push_segment(result: &mut Vec<DirectedSegment>, text: String, direction: Direction) {
+    match result.last_mut() {
+        Some(last) if last.direction == direction => last.text.push_str(&text),
+        _ => result.push(DirectedSegment { text, direction }),
+    }
+}
+
+// Spaces and hyphens inside an RTL phrase belong to it, otherwise "عاصم بن" would be shown as two words in the
+// wrong order
+fn merge_rtl_runs(segments: Vec<DirectedSegment>) -> Vec<DirectedSegment> {
+    let mut result: Vec<DirectedSegment> = Vec::with_capacity(segments.len());
+
+    for (index, segment) in segments.iter().enumerate() {
+        let joins_rtl = segment.direction == Direction::Neutral
+            && segment
+                .text
+                .chars()
+                .all(|c| c == ' ' || c == '-' || c == '_')
+            && index > 0
+            && segments[index - 1].direction == Direction::RightToLeft
+            && segments.get(index + 1).map(|next| next.direction) == Some(Direction::RightToLeft);
+
+        let direction = if joins_rtl {
+            Direction::RightToLeft
+        } else {
+            segment.direction
+        };
+
+        match result.last_mut() {
+            Some(last) if last.direction == direction => last.text.push_str(&segment.text),
+            _ => result.push(DirectedSegment {
+                text: segment.text.clone(),
+                direction,
+            }),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, direction: Direction) -> DirectedSegment {
+        DirectedSegment {
+            text: text.to_string(),
+            direction,
+        }
+    }
+
+    #[test]
+    fn should_split_ltr_text_at_separators() {
+        assert_eq!(
+            directed_segments("example.com"),
+            vec![
+                segment("example", Direction::LeftToRight),
+                segment(".", Direction::Neutral),
+                segment("com", Direction::LeftToRight),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_isolate_rtl_words_from_separators() {
+        assert_eq!(
+            directed_segments("example.com/عاصم/..."),
+            vec![
+                segment("example", Direction::LeftToRight),
+                segment(".", Direction::Neutral),
+                segment("com", Direction::LeftToRight),
+                segment("/", Direction::Neutral),
+                segment("عاصم", Direction::RightToLeft),
+                segment("/...", Direction::Neutral),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_rtl_phrases_together() {
+        assert_eq!(
+            directed_segments("/עברית-ועוד/"),
+            vec![
+                segment("/", Direction::Neutral),
+                segment("עברית-ועוד", Direction::RightToLeft),
+                segment("/", Direction::Neutral),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_treat_numbers_as_neutral() {
+        assert_eq!(
+            directed_segments("123/abc"),
+            vec![
+                segment("123/", Direction::Neutral),
+                segment("abc", Direction::LeftToRight),
+            ]
+        );
+    }
+}
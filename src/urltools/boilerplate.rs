@@ -0,0 +1,82 @@
+use super::Options;
+
+/// Path words that say what kind of page this is but not whose, e.g. the "in" in "/in/jdoe". Ranked from the
+/// one worth keeping longest to the one elided first: words like "channel" at least tell the reader what the
+/// link is, "u" or "~" tell them nothing.
+pub const DEFAULT_BOILERPLATE: &[&str] = &[
+    "channel", "profile", "profiles", "people", "person", "members", "member", "authors", "author",
+    "users", "user", "c", "u", "in", "~",
+];
+
+/// The boilerplate dictionary for the host, a host's own list applies to its subdomains too.
+pub fn dictionary_for<'a>(host: Option<&str>, options: &'a Options) -> &'a [String] {
+    let host = host.unwrap_or("").to_lowercase();
+    options
+        .host_boilerplate
+        .iter()
+        .find(|(domain, _)| {
+            host == *domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .map_or(false, |rest| rest.ends_with('.'))
+        })
+        .map_or(&options.boilerplate, |(_, words)| words)
+}
+
+/// Where the word is in the dictionary, None if it isn't boilerplate. Case doesn't count.
+pub fn rank(word: &str, dictionary: &[String]) -> Option<usize> {
+    dictionary
+        .iter()
+        .position(|entry| entry.eq_ignore_ascii_case(word))
+}
+
+/// Whether the word is an ID made of digits, like the "103914" in "/user/103914".
+pub fn is_numeric_id(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options_with_override() -> Options {
+        Options {
+            host_boilerplate: vec![("example.com".to_string(), vec!["in".to_string()])],
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn should_rank_words_in_dictionary_order() {
+        let options = Options::default();
+        let dictionary = dictionary_for(Some("example.com"), &options);
+
+        assert!(rank("channel", dictionary) < rank("IN", dictionary));
+        assert_eq!(rank("jdoe", dictionary), None);
+    }
+
+    #[test]
+    fn hosts_should_be_able_to_override_dictionary() {
+        let options = options_with_override();
+
+        assert_eq!(
+            dictionary_for(Some("blog.example.com"), &options),
+            &["in".to_string()]
+        );
+        assert_eq!(
+            dictionary_for(Some("notexample.com"), &options).len(),
+            DEFAULT_BOILERPLATE.len()
+        );
+        assert_eq!(
+            dictionary_for(None, &options).len(),
+            DEFAULT_BOILERPLATE.len()
+        );
+    }
+
+    #[test]
+    fn should_recognise_numeric_ids() {
+        assert!(is_numeric_id("103914"));
+        assert!(!is_numeric_id("jdoe42"));
+        assert!(!is_numeric_id(""));
+    }
+}
@@ -0,0 +1,300 @@
+use super::abbreviator::Link;
+use super::segment::{self, Segment};
+use super::{
+    clean_url, decode_path, elide_path, host_candidates, host_form, importance_order, join_path,
+    parse_url, query_and_fragment, rank_components, required_count, schemes, split_path,
+    with_separators,
+};
+use super::{AbbreviateError, Abbreviation, Importance, Options};
+use std::collections::HashSet;
+
+// Each restored component costs length the label rarely has, and combinations of more than two are never
+// what tells two links apart
+const MAX_RESTORED: usize = 2;
+
+/// Brings back path components elided from labels that collide with another link's label, so different
+/// pages don't look the same. Each colliding label gets the smallest set of components that makes it unique
+/// and, to stay within `max_length`, it can give up other components that aren't names. Labels that can't
+/// be told apart within the budget are left as they are, as are the ones `restorable` says aren't paths,
+/// like platform accounts. Case doesn't count, labels that only differ in case are easily mistaken.
+pub fn disambiguate(
+    links: &[Link],
+    labels: &mut [Result<Abbreviation, AbbreviateError>],
+    restorable: &[bool],
+    max_length: usize,
+    options: &Options,
+) {
+    let keys: Vec<Option<String>> = labels.iter().map(label_key).collect();
+
+    for index in 0..labels.len() {
+        let key = match &keys[index] {
+            Some(key) if restorable[index] => key,
+            _ => continue,
+        };
+
+        let collides = keys.iter().enumerate().any(|(other, other_key)| {
+            other_key.as_ref() == Some(key) && links[other].url != links[index].url
+        });
+        if !collides {
+            continue;
+        }
+
+        // Other links may have been restored already, so we check against what they show now
+        let mut taken: HashSet<String> = labels
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .filter_map(|(_, label)| label_key(label))
+            .collect();
+        taken.insert(key.clone());
+
+        let link = &links[index];
+        let restored = match restored_labels(link.url, link.names, max_length, options) {
+            Ok(restored) => restored,
+            Err(_) => continue,
+        };
+
+        if let Some(label) = restored
+            .into_iter()
+            .find(|label| !taken.contains(&label.text.to_lowercase()))
+        {
+            labels[index] = Ok(label);
+        }
+    }
+}
+
+fn label_key(label: &Result<Abbreviation, AbbreviateError>) -> Option<String> {
+    label.as_ref().ok().map(|label| label.text.to_lowercase())
+}
+
+/// Versions of the URL's label with elided path components brought back, fewest first. Components are
+/// restored on top of what `PathElision` keeps, whatever the strategy.
+fn restored_labels(
+    url_string: &str,
+    names: &[&str],
+    max_length: usize,
+    options: &Options,
+) -> Result<Vec<Abbreviation>, AbbreviateError> {
+    let mut url = parse_url(url_string)?;
+    if schemes::scheme_label(&url)?.is_some() {
+        return Ok(vec![]);
+    }
+
+    clean_url(&mut url, names, options)?;
+    let suffix = query_and_fragment(&url, options);
+    let available = max_length.saturating_sub(options.measure.length_of(&suffix));
+
+    let hosts = host_candidates(&url, names, options);
+    let path = decode_path(&url)?;
+    let components = split_path(&path, options.sub_words, options.normaliser.as_ref());
+    let importance = rank_components(&components, url.host_str(), names, options);
+    let by_importance = importance_order(&importance);
+
+    let fits =
+        |segments: &[Segment]| options.measure.length_of(&segment::join(segments)) <= available;
+    let elide = |keep: &[bool]| {
+        elide_path(
+            &components,
+            &importance,
+            &with_separators(&importance, keep),
+            options,
+        )
+    };
+    let keep = |keep_count: usize| {
+        let mut keep = vec![false; components.len()];
+        for index in &by_importance[..keep_count] {
+            keep[*index] = true;
+        }
+        keep
+    };
+
+    // What the label keeps now, as `abbreviate_path` picks it
+    let required = required_count(&importance);
+    let keep_count = (required..=by_importance.len())
+        .rev()
+        .find(|keep_count| fits(&join_path(&hosts[0], &elide(&keep(*keep_count)))))
+        .unwrap_or(required);
+    let kept = keep(keep_count);
+    // Separators come back with the components around them
+    let elided: Vec<usize> = (0..components.len())
+        .filter(|index| importance[*index] != Importance::Separator && !kept[*index])
+        .collect();
+
+    let mut result = vec![];
+    for size in 1..=MAX_RESTORED {
+        for restored in combinations(&elided, size) {
+            let mut keep = kept.clone();
+            for index in &restored {
+                keep[*index] = true;
+            }
+
+            // Less important components make room for the restored ones, least important first
+            let mut droppable = by_importance.iter().rev().filter(|index| {
+                importance[**index] > Importance::Name && !restored.contains(index)
+            });
+
+            loop {
+                let path = elide(&keep);
+                if let Some(mut segments) = hosts
+                    .iter()
+                    .map(|host| join_path(host, &path))
+                    .find(|segments| fits(segments))
+                {
+                    segment::push(&mut segments, Segment::Kept(suffix.clone()));
+                    result.push(Abbreviation::new(segments, host_form(&url)));
+                    break;
+                }
+
+                match droppable.find(|index| keep[**index]) {
+                    Some(index) => keep[*index] = false,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// All the ways of picking `size` items, keeping their order.
+fn combinations(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+
+    let mut result = vec![];
+    for (position, item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[position + 1..], size - 1) {
+            rest.insert(0, *item);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Abbreviator;
+    use super::*;
+
+    fn label_list(urls: &[&str], names: &[&str], max_length: usize) -> Vec<String> {
+        let links: Vec<Link> = urls
+            .iter()
+            .map(|url| Link {
+                url,
+                names,
+                fediverse: None,
+            })
+            .collect();
+
+        Abbreviator::default()
+            .label_list(&links, Some(max_length))
+            .into_iter()
+            .map(|label| label.unwrap().text)
+            .collect()
+    }
+
+    #[test]
+    fn should_restore_components_that_tell_labels_apart() {
+        assert_eq!(
+            label_list(
+                &[
+                    "https://example.com/projects/alpha/overview",
+                    "https://example.com/projects/beta/overview",
+                ],
+                &[],
+                24
+            ),
+            vec!["example.com/.../alpha", "example.com/.../beta"]
+        );
+    }
+
+    #[test]
+    fn should_drop_other_components_to_make_room() {
+        assert_eq!(
+            label_list(
+                &[
+                    "https://example.com/jdoe/talks/2019",
+                    "https://example.com/jdoe/talks/2020",
+                ],
+                &["John", "Doe"],
+                25
+            ),
+            vec!["example.com/jdoe/.../2019", "example.com/jdoe/.../2020"]
+        );
+    }
+
+    #[test]
+    fn should_leave_labels_that_dont_collide() {
+        let urls = [
+            "https://example.com/projects/alpha/overview",
+            "https://example.org/projects/beta/overview",
+            "https://example.com/projects/alpha/overview",
+        ];
+
+        assert_eq!(
+            label_list(&urls, &[], 24),
+            vec![
+                "example.com/projects",
+                "example.org/projects",
+                "example.com/projects"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_leave_collisions_that_dont_fit() {
+        assert_eq!(
+            label_list(
+                &["https://example.com/alpha", "https://example.com/beta"],
+                &[],
+                11
+            ),
+            vec!["example.com", "example.com"]
+        );
+    }
+
+    #[test]
+    fn should_not_restore_into_another_collision() {
+        assert_eq!(
+            label_list(
+                &[
+                    "https://example.com/projects/alpha/overview",
+                    "https://example.com/projects/beta/overview",
+                    "https://example.com/projects/alpha/history",
+                ],
+                &[],
+                24
+            ),
+            vec![
+                "example.com/.../alpha",
+                "example.com/.../beta",
+                "example.com/.../history"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_leave_account_labels() {
+        assert_eq!(
+            label_list(
+                &[
+                    "https://github.com/diegov",
+                    "https://github.com/diegov?tab=repositories",
+                ],
+                &[],
+                30
+            ),
+            vec!["GitHub · diegov", "GitHub · diegov"]
+        );
+    }
+
+    #[test]
+    fn should_pick_combinations_in_order() {
+        assert_eq!(
+            combinations(&[1, 2, 3], 2),
+            vec![vec![1, 2], vec![1, 3], vec![2, 3]]
+        );
+        assert_eq!(combinations(&[1], 2), Vec::<Vec<usize>>::new());
+    }
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::fmt;
+use std::str::Utf8Error;
+
+/// Everything that can go wrong while abbreviating a URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbbreviateError {
+    /// The text is not a valid URL.
+    Parse {
+        url: String,
+        source: url::ParseError,
+    },
+    /// A percent-encoded part of the URL doesn't decode to valid UTF-8.
+    InvalidUtf8 { part: String, source: Utf8Error },
+    /// The host can't be replaced with its shortened version.
+    UnsupportedHost {
+        host: String,
+        source: url::ParseError,
+    },
+    /// A contact or chat URI, like "tel:" or "mailto:", without the address it should show.
+    MissingAddress { url: String },
+}
+
+impl fmt::Display for AbbreviateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbbreviateError::Parse { url, source } => {
+                write!(f, "can't parse URL {:?}: {}", url, source)
+            }
+            AbbreviateError::InvalidUtf8 { part, source } => {
+                write!(f, "{:?} is not valid UTF-8 once decoded: {}", part, source)
+            }
+            AbbreviateError::UnsupportedHost { host, source } => {
+                write!(f, "unsupported host {:?}: {}", host, source)
+            }
+            AbbreviateError::MissingAddress { url } => {
+                write!(f, "{:?} doesn't have an address to show", url)
+            }
+        }
+    }
+}
+
+impl Error for AbbreviateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AbbreviateError::Parse { source, .. } => Some(source),
+            AbbreviateError::InvalidUtf8 { source, .. } => Some(source),
+            AbbreviateError::UnsupportedHost { source, .. } => Some(source),
+            AbbreviateError::MissingAddress { .. } => None,
+        }
+    }
+}
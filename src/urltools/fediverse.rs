@@ -0,0 +1,102 @@
+use super::idn;
+use super::platforms::match_pattern;
+use super::segment::Segment;
+use super::HostForm;
+use url::Host;
+use url::Url;
+
+// Profile paths used by Mastodon, Pleroma, Misskey and most other ActivityPub servers
+const PROFILE_PATTERNS: &[&str] = &["/@{user}", "/users/{user}"];
+// Some servers, e.g. Pixelfed, also have profiles at the root. Too ambiguous to detect, but fine when the
+// site says it's a fediverse account.
+const EXPLICIT_PROFILE_PATTERNS: &[&str] = &["/@{user}", "/users/{user}", "/{user}"];
+
+/// Returns the handle for a fediverse profile URL, e.g. "@alice@mastodon.social" for
+/// "https://mastodon.social/@alice", along with the form the host is shown in. With `explicit`, the caller
+/// knows it's a fediverse account, so more path shapes are accepted.
+pub fn fediverse_handle(url: &Url, explicit: bool) -> Option<(Vec<Segment>, HostForm)> {
+    let domain = match url.host() {
+        Some(Host::Domain(domain)) => domain,
+        _ => return None,
+    };
+
+    let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+    let patterns = if explicit {
+        EXPLICIT_PROFILE_PATTERNS
+    } else {
+        PROFILE_PATTERNS
+    };
+
+    let user = patterns
+        .iter()
+        .find_map(|pattern| match_pattern(pattern, &segments))?;
+
+    // The handle names the server, which is the host as it is, except for the www we never show
+    let domain = domain
+        .strip_prefix("www.")
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(domain);
+    let (host, host_form) = idn::display_host(domain);
+
+    let handle = vec![
+        Segment::User(format!("@{}", user)),
+        Segment::Host(format!("@{}", host)),
+    ];
+    Some((handle, host_form))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::segment;
+    use super::*;
+
+    fn handle(url_string: &str, explicit: bool) -> Option<String> {
+        fediverse_handle(&Url::parse(url_string).unwrap(), explicit)
+            .map(|(handle, _)| segment::join(&handle))
+    }
+
+    #[test]
+    fn should_detect_profile_paths_on_any_host() {
+        assert_eq!(
+            handle("https://mastodon.social/@alice", false).unwrap(),
+            "@alice@mastodon.social"
+        );
+        assert_eq!(
+            handle("https://hachyderm.io/users/alice/", false).unwrap(),
+            "@alice@hachyderm.io"
+        );
+    }
+
+    #[test]
+    fn should_only_accept_root_profiles_when_explicit() {
+        assert_eq!(handle("https://pixelfed.social/alice", false), None);
+        assert_eq!(
+            handle("https://pixelfed.social/alice", true).unwrap(),
+            "@alice@pixelfed.social"
+        );
+    }
+
+    #[test]
+    fn should_not_match_other_paths() {
+        assert_eq!(handle("https://mastodon.social/@alice/109876", false), None);
+        assert_eq!(handle("https://mastodon.social/about", false), None);
+        assert_eq!(handle("http://192.168.1.10/@alice", false), None);
+    }
+
+    #[test]
+    fn should_show_idn_servers_in_unicode() {
+        assert_eq!(
+            fediverse_handle(
+                &Url::parse("https://xn--mnchen-3ya.de/@alice").unwrap(),
+                false
+            ),
+            Some((
+                vec![
+                    Segment::User("@alice".to_string()),
+                    Segment::Host("@münchen.de".to_string())
+                ],
+                HostForm::Unicode
+            ))
+        );
+    }
+}
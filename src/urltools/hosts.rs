@@ -0,0 +1,372 @@
+use super::segment::{self, Segment};
+use super::{matching, Options};
+use std::collections::HashSet;
+
+// Rules from https://publicsuffix.org, both the ICANN and the private sections, so "github.io" is a suffix and
+// "diegov.github.io" a registrable domain. The private section is about a quarter of the list, but it's where
+// the hosts people put their own pages on are. The file says which version of the list it is, update it with
+// scripts/update-public-suffix-list.sh.
+const PUBLIC_SUFFIX_LIST: &str = include_str!("public_suffix_list.dat");
+
+// Subdomains that pick a version of the same site rather than a different site
+const NOISE_LABELS: &[&str] = &["amp", "m", "mobile"];
+// Languages sites commonly use as subdomains, e.g. en.wikipedia.org. Regional variants like "pt-br" are
+// recognised from these.
+const LANGUAGE_CODES: &[&str] = &[
+    "ar", "bg", "ca", "cs", "da", "de", "el", "en", "eo", "es", "et", "eu", "fa", "fi", "fr", "ga",
+    "gl", "he", "hi", "hr", "hu", "id", "it", "ja", "ko", "lt", "lv", "nl", "no", "pl", "pt", "ro",
+    "ru", "sk", "sl", "sr", "sv", "th", "tr", "uk", "vi", "zh",
+];
+
+struct SuffixRules {
+    rules: HashSet<&'static str>,
+    // Stored without the leading "*."
+    wildcards: HashSet<&'static str>,
+    // Stored without the leading "!"
+    exceptions: HashSet<&'static str>,
+}
+
+thread_local! {
+    static SUFFIX_RULES: SuffixRules = SuffixRules::parse(PUBLIC_SUFFIX_LIST);
+}
+
+impl SuffixRules {
+    fn parse(list: &'static str) -> Self {
+        let mut result = SuffixRules {
+            rules: HashSet::new(),
+            wildcards: HashSet::new(),
+            exceptions: HashSet::new(),
+        };
+
+        for line in list.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            if let Some(rule) = line.strip_prefix('!') {
+                result.exceptions.insert(rule);
+            } else if let Some(rule) = line.strip_prefix("*.") {
+                result.wildcards.insert(rule);
+            } else {
+                result.rules.insert(line);
+            }
+        }
+
+        result
+    }
+
+    /// Number of labels in the public suffix of a host, following the list's algorithm: the longest matching
+    /// rule wins, exceptions beat everything else and an unknown TLD is a suffix on its own.
+    fn suffix_length(&self, labels: &[&str]) -> usize {
+        for start in 0..labels.len() {
+            let candidate = labels[start..].join(".");
+            if self.exceptions.contains(candidate.as_str()) {
+                return labels.len() - start - 1;
+            }
+
+            let parent = labels[start + 1..].join(".");
+            if self.rules.contains(candidate.as_str())
+                || (!parent.is_empty() && self.wildcards.contains(parent.as_str()))
+            {
+                return labels.len() - start;
+            }
+        }
+
+        1
+    }
+}
+
+/// A domain split into the labels before the registrable domain, and the registrable domain itself.
+#[derive(Debug, PartialEq)]
+pub struct SplitHost<'a> {
+    pub subdomains: Vec<&'a str>,
+    pub registrable: &'a str,
+}
+
+/// Finds the registrable domain of a host, e.g. "example.co.uk" for "blog.example.co.uk". Hosts that are only
+/// a public suffix, or that have empty labels, are all registrable domain.
+pub fn split_host(host: &str) -> SplitHost {
+    let labels: Vec<&str> = host.split('.').collect();
+    let whole = SplitHost {
+        subdomains: vec![],
+        registrable: host,
+    };
+
+    if labels.iter().any(|label| label.is_empty()) {
+        return whole;
+    }
+
+    // The list has internationalised rules in Unicode, decoding doesn't change the number of labels
+    let (unicode, _) = idna::domain_to_unicode(host);
+    let unicode_labels: Vec<&str> = unicode.split('.').collect();
+    if unicode_labels.len() != labels.len() {
+        return whole;
+    }
+
+    let suffix_length = SUFFIX_RULES.with(|rules| rules.suffix_length(&unicode_labels));
+    if suffix_length >= labels.len() {
+        return whole;
+    }
+
+    let subdomain_count = labels.len() - suffix_length - 1;
+    let registrable_start: usize = labels[..subdomain_count]
+        .iter()
+        .map(|label| label.len() + 1)
+        .sum();
+
+    SplitHost {
+        subdomains: labels[..subdomain_count].to_vec(),
+        registrable: &host[registrable_start..],
+    }
+}
+
+/// Removes the leading subdomains that only pick a language or mobile version of the site, e.g. "en.m" in
+/// "en.m.wikipedia.org". Returns None when there's nothing to remove.
+pub fn strip_noise(host: &str, names: &[&str], options: &Options) -> Option<String> {
+    let split = split_host(host);
+    let names = normalised_names(names, options);
+
+    let noise_count = split
+        .subdomains
+        .iter()
+        .take_while(|label| is_noise(label) && !is_name(label, &names, options))
+        .count();
+
+    if noise_count == 0 {
+        return None;
+    }
+
+    let mut labels = split.subdomains[noise_count..].to_vec();
+    labels.push(split.registrable);
+    Some(labels.join("."))
+}
+
+/// Returns shortened versions of the host, from the most complete to the least complete one. Subdomains are
+/// elided from the right, so the first (usually most specific) ones survive longest, and those matching the
+/// names are never elided. The registrable domain is always kept whole.
+pub fn host_candidates(host: &str, names: &[&str], options: &Options) -> Vec<Vec<Segment>> {
+    let split = split_host(host);
+    let names = normalised_names(names, options);
+    let is_name: Vec<bool> = split
+        .subdomains
+        .iter()
+        .map(|label| is_name(label, &names, options))
+        .collect();
+
+    let required = is_name.iter().filter(|is_name| **is_name).count();
+
+    (required..=split.subdomains.len())
+        .rev()
+        .map(|keep_count| {
+            let mut others = keep_count - required;
+            let keep: Vec<bool> = is_name
+                .iter()
+                .map(|is_name| {
+                    if *is_name {
+                        true
+                    } else if others > 0 {
+                        others -= 1;
+                        true
+                    } else {
+                        false
+                    }
+                })
+                .collect();
+
+            elide_host(&split, &is_name, &keep, &options.ellipsis)
+        })
+        .collect()
+}
+
+fn elide_host(split: &SplitHost, is_name: &[bool], keep: &[bool], ellipsis: &str) -> Vec<Segment> {
+    let mut result = vec![];
+    let mut elided: Vec<&str> = vec![];
+    // An elided run takes its dots with it, e.g. "very...example.co.uk", so we only add a dot once we know the
+    // next label is kept
+    let mut pending_dot = false;
+
+    for (index, label) in split.subdomains.iter().enumerate() {
+        if keep[index] {
+            push_separator(&mut result, &mut elided, pending_dot, ellipsis);
+            let label = label.to_string();
+            if is_name[index] {
+                segment::push(&mut result, Segment::NameMatch(label));
+            } else {
+                segment::push(&mut result, Segment::Host(label));
+            }
+            pending_dot = true;
+        } else {
+            elided.push(label);
+        }
+    }
+
+    push_separator(&mut result, &mut elided, pending_dot, ellipsis);
+    segment::push(&mut result, Segment::Host(split.registrable.to_string()));
+    result
+}
+
+/// Adds what goes between two kept labels, either a dot or the ellipsis for the labels elided between them.
+fn push_separator(
+    result: &mut Vec<Segment>,
+    elided: &mut Vec<&str>,
+    pending_dot: bool,
+    ellipsis: &str,
+) {
+    if !elided.is_empty() {
+        result.push(Segment::Elided {
+            text: ellipsis.to_string(),
+            original: elided.join("."),
+        });
+        elided.clear();
+    } else if pending_dot {
+        segment::push(result, Segment::Host(".".to_string()));
+    }
+}
+
+fn is_noise(label: &str) -> bool {
+    let label = label.to_lowercase();
+    if NOISE_LABELS.contains(&label.as_str()) {
+        return true;
+    }
+
+    let mut parts = label.splitn(2, '-');
+    let language = parts.next().unwrap_or("");
+    let region_ok = parts
+        .next()
+        .map_or(true, |region| region.len() == 2 || region.len() == 4);
+
+    LANGUAGE_CODES.contains(&language) && region_ok
+}
+
+fn normalised_names(names: &[&str], options: &Options) -> Vec<String> {
+    names
+        .iter()
+        .map(|name| options.normaliser.normalise(name))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn is_name(label: &str, names: &[String], options: &Options) -> bool {
+    matching::best_match(&options.normaliser.normalise(label), names).map_or(false, |name_match| {
+        name_match.score >= options.name_match_threshold
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registrable(host: &str) -> &str {
+        split_host(host).registrable
+    }
+
+    #[test]
+    fn should_find_registrable_domain() {
+        assert_eq!(registrable("en.wikipedia.org"), "wikipedia.org");
+        assert_eq!(registrable("blog.example.co.uk"), "example.co.uk");
+        assert_eq!(registrable("example.co.uk"), "example.co.uk");
+        assert_eq!(split_host("a.b.example.com").subdomains, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn should_use_private_suffixes() {
+        assert_eq!(registrable("diegov.github.io"), "diegov.github.io");
+        assert_eq!(registrable("www.diegov.github.io"), "diegov.github.io");
+    }
+
+    #[test]
+    fn should_apply_wildcards_and_exceptions() {
+        assert_eq!(registrable("a.b.c.ck"), "b.c.ck");
+        assert_eq!(registrable("a.www.ck"), "www.ck");
+    }
+
+    #[test]
+    fn should_treat_unknown_tlds_as_suffixes() {
+        assert_eq!(registrable("a.example.notatld"), "example.notatld");
+        assert_eq!(registrable("localhost"), "localhost");
+        assert_eq!(registrable("co.uk"), "co.uk");
+        assert_eq!(registrable("www."), "www.");
+    }
+
+    #[test]
+    fn should_use_unicode_rules_for_punycode_hosts() {
+        // "公司.cn" is a suffix
+        assert_eq!(
+            registrable("a.example.xn--55qx5d.cn"),
+            "example.xn--55qx5d.cn"
+        );
+    }
+
+    #[test]
+    fn should_strip_language_and_mobile_subdomains() {
+        let options = Options::default();
+        assert_eq!(
+            strip_noise("en.m.wikipedia.org", &[], &options),
+            Some("wikipedia.org".to_string())
+        );
+        assert_eq!(
+            strip_noise("pt-br.example.com", &[], &options),
+            Some("example.com".to_string())
+        );
+        assert_eq!(strip_noise("blog.en.example.com", &[], &options), None);
+        // Part of the registrable domain, not a subdomain
+        assert_eq!(strip_noise("m.com", &[], &options), None);
+    }
+
+    #[test]
+    fn should_not_strip_subdomains_matching_names() {
+        let options = Options::default();
+        assert_eq!(
+            strip_noise("es.example.com", &["Es", "Smith"], &options),
+            None
+        );
+    }
+
+    fn candidates(host: &str, names: &[&str]) -> Vec<String> {
+        host_candidates(host, names, &Options::default())
+            .iter()
+            .map(|candidate| segment::join(candidate))
+            .collect()
+    }
+
+    #[test]
+    fn host_candidates_should_elide_from_the_right() {
+        assert_eq!(
+            candidates("very.long.corp.example.co.uk", &[]),
+            vec![
+                "very.long.corp.example.co.uk",
+                "very.long...example.co.uk",
+                "very...example.co.uk",
+                "...example.co.uk",
+            ]
+        );
+    }
+
+    #[test]
+    fn host_candidates_should_keep_names() {
+        assert_eq!(
+            candidates("jdoe.internal.example.com", &["John", "Doe"]),
+            vec!["jdoe.internal.example.com", "jdoe...example.com"]
+        );
+        assert_eq!(
+            candidates("diegov.github.io", &[]),
+            vec!["diegov.github.io"]
+        );
+    }
+
+    #[test]
+    fn host_candidates_should_tell_names_and_elided_labels_apart() {
+        let options = Options::default();
+        assert_eq!(
+            host_candidates("jdoe.internal.example.com", &["John", "Doe"], &options)[1],
+            vec![
+                Segment::NameMatch("jdoe".to_string()),
+                Segment::Elided {
+                    text: "...".to_string(),
+                    original: "internal".to_string()
+                },
+                Segment::Host("example.com".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use unicode_script::{Script, UnicodeScript};
+
+const ACE_PREFIX: &str = "xn--";
+
+/// Which form of the host we show to the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostForm {
+    /// Plain ASCII host, there was nothing to decode.
+    Ascii,
+    /// Internationalised host, decoded to Unicode.
+    Unicode,
+    /// Internationalised host we keep in punycode, because it could be imitating another host. Links
+    /// with this form deserve a warning.
+    Punycode,
+}
+
+// Script combinations that are normal in a single label, as in the browsers' IDN display policies. Common and
+// Inherited characters (digits, hyphens, combining marks...) can go with anything and are ignored.
+const ALLOWED_SCRIPT_MIXES: &[&[Script]] = &[
+    &[
+        Script::Latin,
+        Script::Han,
+        Script::Hiragana,
+        Script::Katakana,
+    ],
+    &[Script::Latin, Script::Han, Script::Bopomofo],
+    &[Script::Latin, Script::Han, Script::Hangul],
+];
+
+// Cyrillic letters that are indistinguishable from Latin ones. A label written only with these, like "аррӏе",
+// is a whole-script imitation of a Latin label.
+const LATIN_LOOKALIKES: &str = "аԁеһіјӏорԛѕԝхус";
+
+/// Returns the host as we want to display it, and the form we chose.
+pub fn display_host(domain: &str) -> (String, HostForm) {
+    if !domain
+        .split('.')
+        .any(|label| label.to_lowercase().starts_with(ACE_PREFIX))
+    {
+        return (domain.to_string(), HostForm::Ascii);
+    }
+
+    let (unicode, result) = idna::domain_to_unicode(domain);
+    if result.is_err() {
+        return (domain.to_string(), HostForm::Punycode);
+    }
+
+    // A Cyrillic label that looks Latin is only suspicious next to a Latin TLD
+    let latin_tld = unicode
+        .rsplit('.')
+        .next()
+        .map_or(true, |tld| tld.is_ascii());
+
+    if unicode
+        .split('.')
+        .all(|label| is_safe_label(label, latin_tld))
+    {
+        (unicode, HostForm::Unicode)
+    } else {
+        (domain.to_string(), HostForm::Punycode)
+    }
+}
+
+fn is_safe_label(label: &str, latin_tld: bool) -> bool {
+    let mut scripts: Vec<Script> = vec![];
+
+    for c in label.chars() {
+        let script = c.script();
+        if script != Script::Common && script != Script::Inherited && !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+
+    if scripts.len() > 1 {
+        return ALLOWED_SCRIPT_MIXES
+            .iter()
+            .any(|allowed| scripts.iter().all(|script| allowed.contains(script)));
+    }
+
+    let is_latin_lookalike = scripts == [Script::Cyrillic]
+        && label
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .all(|c| LATIN_LOOKALIKES.contains(c));
+
+    !(latin_tld && is_latin_lookalike)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_ascii_hosts() {
+        assert_eq!(
+            display_host("example.com"),
+            ("example.com".to_string(), HostForm::Ascii)
+        );
+    }
+
+    #[test]
+    fn should_decode_single_script_labels() {
+        assert_eq!(
+            display_host("xn--mnchen-3ya.de"),
+            ("münchen.de".to_string(), HostForm::Unicode)
+        );
+        assert_eq!(
+            display_host("xn--e1afmkfd.xn--p1ai"),
+            ("пример.рф".to_string(), HostForm::Unicode)
+        );
+    }
+
+    #[test]
+    fn should_decode_allowed_script_mixes() {
+        assert_eq!(
+            display_host("xn--abc-v08fl0dtz6h.jp"),
+            ("abc日本語.jp".to_string(), HostForm::Unicode)
+        );
+    }
+
+    #[test]
+    fn should_keep_punycode_for_mixed_scripts() {
+        // Latin "p" and "l" with Cyrillic "а" and "у"
+        assert_eq!(
+            display_host("xn--pl-7kc1f.com"),
+            ("xn--pl-7kc1f.com".to_string(), HostForm::Punycode)
+        );
+    }
+
+    #[test]
+    fn should_keep_punycode_for_whole_script_lookalikes() {
+        // "аррӏе" in Cyrillic
+        assert_eq!(
+            display_host("xn--80ak6aa92e.com"),
+            ("xn--80ak6aa92e.com".to_string(), HostForm::Punycode)
+        );
+    }
+
+    #[test]
+    fn should_keep_punycode_for_invalid_labels() {
+        assert_eq!(
+            display_host("xn--a.com"),
+            ("xn--a.com".to_string(), HostForm::Punycode)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+// Scores for each kind of match, anything below the configured threshold is not considered a name
+const EXACT_SCORE: f64 = 1.0;
+const CONCATENATION_SCORE: f64 = 0.95;
+const INITIAL_SCORE: f64 = 0.9;
+const PREFIX_BASE_SCORE: f64 = 0.5;
+const PREFIX_RATIO_SCORE: f64 = 0.4;
+const EDIT_BASE_SCORE: f64 = 0.8;
+const EDIT_PENALTY: f64 = 0.1;
+
+// Shorter prefixes and words match far too many things
+const MIN_PREFIX_LENGTH: usize = 3;
+const MIN_EDIT_LENGTH: usize = 4;
+const LONG_WORD_LENGTH: usize = 8;
+
+/// How well a path component matches the names, and which names it used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NameMatch {
+    /// From 0 (unrelated) to 1 (exact match).
+    pub score: f64,
+    /// Indices of the names found in the component, e.g. both names for "jdoe".
+    pub names: Vec<usize>,
+}
+
+/// Finds the best way in which a normalised path component matches the normalised names, if any. Recognises
+/// exact matches, concatenations ("johndoe", "doejohn"), initials with a full name ("jdoe", "johnd"),
+/// prefixes ("chris") and small typos.
+pub fn best_match(component: &str, names: &[String]) -> Option<NameMatch> {
+    if component.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<NameMatch> = None;
+    let mut consider = |score: f64, names: Vec<usize>| {
+        if best.as_ref().map_or(true, |best| score > best.score) {
+            best = Some(NameMatch { score, names });
+        }
+    };
+
+    for (i, name) in names.iter().enumerate() {
+        if component == name {
+            consider(EXACT_SCORE, vec![i]);
+        }
+
+        for (j, other) in names.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+
+            if is_concatenation(component, name, other) {
+                consider(CONCATENATION_SCORE, vec![i, j]);
+            }
+
+            if let Some(initial) = initial(name) {
+                if is_concatenation(component, initial, other)
+                    || is_concatenation(component, other, initial)
+                {
+                    consider(INITIAL_SCORE, vec![i, j]);
+                }
+            }
+        }
+
+        if let Some(score) = prefix_score(component, name) {
+            consider(score, vec![i]);
+        }
+
+        if let Some(score) = edit_score(component, name) {
+            consider(score, vec![i]);
+        }
+    }
+
+    best
+}
+
+pub fn initial(name: &str) -> Option<&str> {
+    name.chars().next().map(|c| &name[..c.len_utf8()])
+}
+
+fn is_concatenation(text: &str, first: &str, second: &str) -> bool {
+    text.len() == first.len() + second.len() && text.starts_with(first) && text.ends_with(second)
+}
+
+fn prefix_score(component: &str, name: &str) -> Option<f64> {
+    let (shorter, longer) = if component.len() < name.len() {
+        (component, name)
+    } else {
+        (name, component)
+    };
+
+    let shorter_length = shorter.chars().count();
+    if shorter == longer || shorter_length < MIN_PREFIX_LENGTH || !longer.starts_with(shorter) {
+        return None;
+    }
+
+    let ratio = shorter_length as f64 / longer.chars().count() as f64;
+    Some(PREFIX_BASE_SCORE + PREFIX_RATIO_SCORE * ratio)
+}
+
+fn edit_score(component: &str, name: &str) -> Option<f64> {
+    let component: Vec<char> = component.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    if component.len().min(name.len()) < MIN_EDIT_LENGTH {
+        return None;
+    }
+
+    let allowed = if component.len().max(name.len()) >= LONG_WORD_LENGTH {
+        2
+    } else {
+        1
+    };
+
+    match edit_distance(&component, &name) {
+        0 => None,
+        distance if distance <= allowed => Some(EDIT_BASE_SCORE - EDIT_PENALTY * distance as f64),
+        _ => None,
+    }
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // Levenshtein distance, keeping only the previous row
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names() -> Vec<String> {
+        vec!["john".to_string(), "doe".to_string()]
+    }
+
+    fn score(component: &str) -> f64 {
+        best_match(component, &names()).map_or(0.0, |m| m.score)
+    }
+
+    #[test]
+    fn should_match_exact_names() {
+        assert_eq!(
+            best_match("doe", &names()),
+            Some(NameMatch {
+                score: EXACT_SCORE,
+                names: vec![1]
+            })
+        );
+    }
+
+    #[test]
+    fn should_match_concatenations_in_any_order() {
+        assert_eq!(score("johndoe"), CONCATENATION_SCORE);
+        assert_eq!(score("doejohn"), CONCATENATION_SCORE);
+        assert_eq!(best_match("doejohn", &names()).unwrap().names, vec![1, 0]);
+    }
+
+    #[test]
+    fn should_match_initials_with_names() {
+        assert_eq!(score("jdoe"), INITIAL_SCORE);
+        assert_eq!(score("johnd"), INITIAL_SCORE);
+        assert_eq!(score("doej"), INITIAL_SCORE);
+        assert_eq!(best_match("jdoe", &names()).unwrap().names, vec![0, 1]);
+    }
+
+    #[test]
+    fn should_score_prefixes_by_length() {
+        let names = vec!["christopher".to_string()];
+        let chris = best_match("chris", &names).unwrap().score;
+        let christoph = best_match("christoph", &names).unwrap().score;
+        assert!(chris < christoph);
+        assert!(christoph < INITIAL_SCORE);
+        assert_eq!(best_match("ch", &names), None);
+    }
+
+    #[test]
+    fn should_match_small_typos() {
+        assert!((score("jonn") - 0.7).abs() < 1e-9);
+        let names = vec!["christopher".to_string()];
+        assert!((best_match("christofer", &names).unwrap().score - 0.6).abs() < 1e-9);
+        assert_eq!(best_match("kristofer", &names), None);
+        // Short names need to match exactly, there are too many 3 letter words one edit away
+        assert_eq!(score("dog"), 0.0);
+    }
+
+    #[test]
+    fn should_not_match_unrelated_components() {
+        assert_eq!(best_match("userstuff", &names()), None);
+        assert_eq!(best_match("", &names()), None);
+        assert_eq!(best_match("jd", &names()), None);
+    }
+
+    #[test]
+    fn edit_distance_should_count_operations() {
+        let chars = |s: &str| s.chars().collect::<Vec<char>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("abc"), &chars("abc")), 0);
+    }
+}
@@ -0,0 +1,50 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// How the length of the text shown to the user is measured, for every length decision we make while abbreviating.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Measure {
+    /// Extended grapheme clusters, which is what a reader would count as characters.
+    Graphemes,
+    /// Columns taken in a monospaced font, East Asian wide characters count as 2.
+    DisplayWidth,
+}
+
+impl Default for Measure {
+    fn default() -> Self {
+        Measure::Graphemes
+    }
+}
+
+impl Measure {
+    pub fn length_of(&self, text: &str) -> usize {
+        match self {
+            Measure::Graphemes => text.graphemes(true).count(),
+            Measure::DisplayWidth => text.width(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graphemes_should_count_combining_characters_once() {
+        assert_eq!(Measure::Graphemes.length_of("cafe\u{301}"), 4);
+        assert_eq!(Measure::Graphemes.length_of("café"), 4);
+    }
+
+    #[test]
+    fn graphemes_should_count_characters_not_bytes() {
+        assert_eq!(Measure::Graphemes.length_of("عاصم"), 4);
+        assert_eq!(Measure::Graphemes.length_of("日本"), 2);
+    }
+
+    #[test]
+    fn display_width_should_count_wide_characters_twice() {
+        assert_eq!(Measure::DisplayWidth.length_of("日本"), 4);
+        assert_eq!(Measure::DisplayWidth.length_of("abcd"), 4);
+        assert_eq!(Measure::DisplayWidth.length_of("cafe\u{301}"), 4);
+    }
+}
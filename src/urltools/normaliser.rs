@@ -0,0 +1,88 @@
+use std::fmt::Debug;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_script::{Script, UnicodeScript};
+use unidecode::unidecode;
+
+/// Turns names and path words into the form we compare them in. Two texts the reader would consider the
+/// same name should normalise to the same string.
+pub trait Normaliser: Debug {
+    fn normalise(&self, text: &str) -> String;
+}
+
+/// Lowercases and transliterates to ASCII, so "Álvaro" matches "alvaro" and "Ǆ" matches "dz". Lossy for
+/// non-Latin scripts, e.g. "عاصم" becomes "`Sm", which makes unrelated names collide.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Transliterate;
+
+/// Unicode NFKC with lowercasing, which keeps the script but folds compatibility forms, e.g. full width
+/// "ＪＯＨＮ" and the "ﬁ" ligature.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CaseFold;
+
+/// Keeps the script and only removes diacritics, e.g. "Álvaro" matches "alvaro" and the vowel marks in
+/// "عَاصِم" are dropped, but "عاصم" is left alone.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StripDiacritics;
+
+impl Normaliser for Transliterate {
+    fn normalise(&self, text: &str) -> String {
+        unidecode(&text.to_lowercase())
+    }
+}
+
+impl Normaliser for CaseFold {
+    fn normalise(&self, text: &str) -> String {
+        // Lowercasing can produce text that isn't NFKC anymore, e.g. "İ" becomes "i" and a combining dot
+        text.nfkc()
+            .collect::<String>()
+            .to_lowercase()
+            .nfkc()
+            .collect()
+    }
+}
+
+impl Normaliser for StripDiacritics {
+    fn normalise(&self, text: &str) -> String {
+        // Only marks shared between scripts are diacritics. Script specific ones, like Devanagari vowel signs,
+        // are part of the letter.
+        text.to_lowercase()
+            .nfd()
+            .filter(|c| !(is_combining_mark(*c) && c.script() == Script::Inherited))
+            .nfc()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transliterate_should_convert_to_ascii() {
+        assert_eq!(Transliterate.normalise("Álvaro"), "alvaro");
+        assert_eq!(Transliterate.normalise("李"), Transliterate.normalise("理"));
+    }
+
+    #[test]
+    fn case_fold_should_keep_script() {
+        assert_eq!(CaseFold.normalise("عاصم"), "عاصم");
+        assert_eq!(CaseFold.normalise("Álvaro"), "álvaro");
+        assert_ne!(CaseFold.normalise("李"), CaseFold.normalise("理"));
+    }
+
+    #[test]
+    fn case_fold_should_fold_compatibility_forms() {
+        assert_eq!(CaseFold.normalise("ＪＯＨＮ"), "john");
+        assert_eq!(CaseFold.normalise("ﬁnn"), "finn");
+    }
+
+    #[test]
+    fn strip_diacritics_should_only_remove_diacritics() {
+        assert_eq!(StripDiacritics.normalise("Álvaro"), "alvaro");
+        assert_eq!(StripDiacritics.normalise("Ελένη"), "ελενη");
+        assert_eq!(StripDiacritics.normalise("عَاصِم"), "عاصم");
+        // Vowel sign, not a diacritic
+        assert_eq!(StripDiacritics.normalise("गुप्ता"), "गुप्ता");
+    }
+}
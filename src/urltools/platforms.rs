@@ -0,0 +1,344 @@
+use super::segment::Segment;
+use percent_encoding::percent_decode;
+use url::Host;
+use url::Url;
+
+const LABEL_SEPARATOR: &str = " · ";
+const USER_PLACEHOLDER: &str = "{user}";
+const ANY_SEGMENT: &str = "*";
+
+/// A well known site, and the path shapes under which we can find a user name in it.
+///
+/// Patterns are matched segment by segment against the whole path, ignoring empty segments:
+/// `*` matches any single segment, `{user}` captures the segment that will be shown in the label,
+/// optionally after a literal prefix (e.g. `@{user}`), and anything else must match literally.
+pub struct Platform {
+    pub name: &'static str,
+    pub hosts: &'static [&'static str],
+    pub patterns: &'static [&'static str],
+    /// Pages of the site itself where a user could be, e.g. "settings" in github.com/settings. Case doesn't
+    /// count.
+    pub reserved: &'static [&'static str],
+}
+
+// Twitter and X are the same site
+const TWITTER_RESERVED: &[&str] = &[
+    "compose",
+    "explore",
+    "home",
+    "i",
+    "login",
+    "messages",
+    "notifications",
+    "privacy",
+    "search",
+    "settings",
+    "signup",
+    "tos",
+];
+
+// To support a new site just add it here, the matcher doesn't need to know about it.
+pub const PLATFORMS: &[Platform] = &[
+    Platform {
+        name: "GitHub",
+        hosts: &["github.com"],
+        patterns: &["/{user}"],
+        reserved: &[
+            "about",
+            "account",
+            "apps",
+            "collections",
+            "contact",
+            "dashboard",
+            "enterprise",
+            "explore",
+            "features",
+            "issues",
+            "join",
+            "login",
+            "logout",
+            "marketplace",
+            "new",
+            "notifications",
+            "organizations",
+            "orgs",
+            "pricing",
+            "pulls",
+            "search",
+            "security",
+            "settings",
+            "signup",
+            "sponsors",
+            "topics",
+            "trending",
+        ],
+    },
+    Platform {
+        name: "GitLab",
+        hosts: &["gitlab.com"],
+        patterns: &["/{user}", "/users/{user}"],
+        reserved: &[
+            "admin",
+            "dashboard",
+            "explore",
+            "groups",
+            "help",
+            "projects",
+            "search",
+            "sign_in",
+            "sign_up",
+            "snippets",
+        ],
+    },
+    Platform {
+        name: "Bitbucket",
+        hosts: &["bitbucket.org"],
+        patterns: &["/{user}"],
+        reserved: &[
+            "account",
+            "blog",
+            "dashboard",
+            "product",
+            "repo",
+            "site",
+            "snippets",
+            "support",
+        ],
+    },
+    Platform {
+        name: "Codeberg",
+        hosts: &["codeberg.org"],
+        patterns: &["/{user}"],
+        reserved: &[
+            "admin",
+            "explore",
+            "issues",
+            "notifications",
+            "pulls",
+            "repo",
+            "user",
+        ],
+    },
+    Platform {
+        name: "SourceHut",
+        hosts: &["sr.ht", "git.sr.ht"],
+        patterns: &["/~{user}"],
+        reserved: &[],
+    },
+    Platform {
+        name: "LinkedIn",
+        hosts: &["linkedin.com"],
+        patterns: &["/in/{user}", "/company/{user}"],
+        reserved: &[],
+    },
+    Platform {
+        name: "Stack Overflow",
+        hosts: &["stackoverflow.com"],
+        patterns: &["/users/*/{user}", "/users/{user}"],
+        reserved: &[],
+    },
+    Platform {
+        name: "Keybase",
+        hosts: &["keybase.io"],
+        patterns: &["/{user}"],
+        reserved: &[
+            "account", "blog", "docs", "download", "jobs", "login", "signup",
+        ],
+    },
+    Platform {
+        name: "Twitter",
+        hosts: &["twitter.com", "mobile.twitter.com"],
+        patterns: &["/{user}"],
+        reserved: TWITTER_RESERVED,
+    },
+    Platform {
+        name: "X",
+        hosts: &["x.com"],
+        patterns: &["/{user}"],
+        reserved: TWITTER_RESERVED,
+    },
+    Platform {
+        name: "YouTube",
+        hosts: &["youtube.com", "m.youtube.com"],
+        patterns: &["/@{user}", "/c/{user}", "/user/{user}", "/channel/{user}"],
+        reserved: &[],
+    },
+    Platform {
+        name: "Reddit",
+        hosts: &["reddit.com", "old.reddit.com"],
+        patterns: &["/user/{user}", "/u/{user}"],
+        reserved: &[],
+    },
+    Platform {
+        name: "Medium",
+        hosts: &["medium.com"],
+        patterns: &["/@{user}"],
+        reserved: &[],
+    },
+    Platform {
+        name: "Instagram",
+        hosts: &["instagram.com"],
+        patterns: &["/{user}"],
+        reserved: &[
+            "about",
+            "accounts",
+            "developer",
+            "direct",
+            "explore",
+            "legal",
+            "p",
+            "reels",
+            "stories",
+            "tv",
+        ],
+    },
+];
+
+/// Returns a label such as "GitHub · diegov" if the URL points to a user in one of the known platforms.
+pub fn platform_label(url: &Url) -> Option<Vec<Segment>> {
+    let host = match url.host() {
+        Some(Host::Domain(domain)) => domain,
+        _ => return None,
+    };
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    let platform = PLATFORMS
+        .iter()
+        .find(|platform| platform.hosts.iter().any(|h| h.eq_ignore_ascii_case(host)))?;
+
+    let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+
+    platform
+        .patterns
+        .iter()
+        .find_map(|pattern| match_pattern(pattern, &segments))
+        .filter(|user| {
+            !platform
+                .reserved
+                .iter()
+                .any(|reserved| reserved.eq_ignore_ascii_case(user))
+        })
+        .map(|user| {
+            vec![
+                Segment::Host(platform.name.to_string()),
+                Segment::Kept(LABEL_SEPARATOR.to_string()),
+                Segment::User(user),
+            ]
+        })
+}
+
+/// Matches the path segments against a pattern as described in `Platform`, returning the user.
+pub fn match_pattern(pattern: &str, segments: &[&str]) -> Option<String> {
+    let parts: Vec<&str> = pattern.split('/').filter(|p| !p.is_empty()).collect();
+    if parts.len() != segments.len() {
+        return None;
+    }
+
+    let mut user = None;
+
+    for (part, segment) in parts.iter().zip(segments) {
+        if *part == ANY_SEGMENT {
+            continue;
+        }
+
+        if let Some(prefix) = part.strip_suffix(USER_PLACEHOLDER) {
+            // get can fail if the prefix length falls inside a multibyte character
+            let (segment_prefix, rest) =
+                match (segment.get(..prefix.len()), segment.get(prefix.len()..)) {
+                    (Some(segment_prefix), Some(rest)) => (segment_prefix, rest),
+                    _ => return None,
+                };
+
+            if rest.is_empty() || !segment_prefix.eq_ignore_ascii_case(prefix) {
+                return None;
+            }
+
+            user = Some(
+                percent_decode(rest.as_bytes())
+                    .decode_utf8()
+                    .ok()?
+                    .into_owned(),
+            );
+        } else if !part.eq_ignore_ascii_case(segment) {
+            return None;
+        }
+    }
+
+    user
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::segment;
+    use super::*;
+
+    fn label(url_string: &str) -> Option<String> {
+        platform_label(&Url::parse(url_string).unwrap()).map(|label| segment::join(&label))
+    }
+
+    #[test]
+    fn should_label_github_user() {
+        assert_eq!(
+            label("https://github.com/diegov").unwrap(),
+            "GitHub · diegov"
+        );
+    }
+
+    #[test]
+    fn should_ignore_www_and_trailing_slash() {
+        assert_eq!(
+            label("https://www.linkedin.com/in/diegov/").unwrap(),
+            "LinkedIn · diegov"
+        );
+    }
+
+    #[test]
+    fn should_skip_wildcard_segments() {
+        assert_eq!(
+            label("https://stackoverflow.com/users/123456/diego-v").unwrap(),
+            "Stack Overflow · diego-v"
+        );
+    }
+
+    #[test]
+    fn should_strip_literal_prefix() {
+        assert_eq!(
+            label("https://www.youtube.com/@diegov").unwrap(),
+            "YouTube · diegov"
+        );
+        assert_eq!(
+            label("https://sr.ht/~diegov").unwrap(),
+            "SourceHut · diegov"
+        );
+    }
+
+    #[test]
+    fn should_decode_user() {
+        assert_eq!(
+            label("https://gitlab.com/d%C3%ADego").unwrap(),
+            "GitLab · díego"
+        );
+    }
+
+    #[test]
+    fn should_not_match_other_path_shapes() {
+        assert_eq!(label("https://github.com/diegov/wasm_site"), None);
+        assert_eq!(label("https://github.com/"), None);
+        assert_eq!(label("https://www.linkedin.com/diegov"), None);
+        assert_eq!(label("https://www.youtube.com/diegov"), None);
+    }
+
+    #[test]
+    fn should_not_match_pages_of_the_site() {
+        assert_eq!(label("https://github.com/about"), None);
+        assert_eq!(label("https://github.com/Settings/"), None);
+        assert_eq!(label("https://x.com/explore"), None);
+        assert_eq!(label("https://gitlab.com/users/sign_in"), None);
+    }
+
+    #[test]
+    fn should_not_match_unknown_hosts() {
+        assert_eq!(label("https://githubs.coms/12345601"), None);
+        assert_eq!(label("https://notgithub.com/diegov"), None);
+    }
+}
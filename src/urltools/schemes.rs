@@ -0,0 +1,244 @@
+use super::error::AbbreviateError;
+use super::segment::Segment;
+use percent_encoding::percent_decode;
+use url::Url;
+
+const MAIL_PREFIX: &str = "✉ ";
+const PLACE_PREFIX: &str = "📍 ";
+const LABEL_SEPARATOR: &str = " · ";
+// Characters people use to group the digits in a phone number, we show spaces instead
+const PHONE_SEPARATORS: &[char] = &['-', '.', '(', ')', ' '];
+
+/// Returns how we show URIs whose scheme doesn't have a host and a path worth showing, e.g.
+/// "✉ me@example.com" for "mailto:me@example.com". None for any other scheme, including http. A label
+/// without the address, "✉ " for "mailto:", would be a link showing nothing, so that's an error.
+pub fn scheme_label(url: &Url) -> Result<Option<Vec<Segment>>, AbbreviateError> {
+    let missing = || AbbreviateError::MissingAddress {
+        url: url.to_string(),
+    };
+    let address = match url.scheme() {
+        "mailto" => decoded_path(url),
+        "tel" => phone_number(&decoded_path(url)),
+        "xmpp" => decoded_path(url).trim_start_matches('/').to_string(),
+        "matrix" => match matrix_id(&decoded_path(url)) {
+            Some(id) => id,
+            // Not one of the identifiers we know, shown like any other URL
+            None => return Ok(None),
+        },
+        "irc" | "ircs" => return irc_target(url).map(Some).ok_or_else(missing),
+        "geo" => return Ok(Some(vec![Segment::Kept(coordinates(&decoded_path(url)))])),
+        _ => return Ok(None),
+    };
+
+    if address.is_empty() {
+        return Err(missing());
+    }
+
+    let mut label = vec![];
+    if url.scheme() == "mailto" {
+        label.push(Segment::Kept(MAIL_PREFIX.to_string()));
+    }
+    label.push(Segment::User(address));
+    Ok(Some(label))
+}
+
+fn decoded_path(url: &Url) -> String {
+    percent_decode(url.path().as_bytes())
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// "+44-20-7946-0958;ext=12" becomes "+44 20 7946 0958 ext. 12", other parameters are dropped.
+fn phone_number(path: &str) -> String {
+    let mut parts = path.split(';');
+    let number = parts
+        .next()
+        .unwrap_or("")
+        .split(PHONE_SEPARATORS)
+        .filter(|group| !group.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    match parts.find_map(|param| param.strip_prefix("ext=")) {
+        Some(extension) => format!("{} ext. {}", number, extension),
+        None => number,
+    }
+}
+
+/// Turns the paths of the matrix: scheme back into the identifiers people know, e.g. "u/alice:matrix.org"
+/// into "@alice:matrix.org". Anything after the first identifier (events, mostly) is dropped. Empty when the
+/// kind of identifier is there but not the identifier itself, or when there's no path at all.
+fn matrix_id(path: &str) -> Option<String> {
+    let mut segments = path.split('/');
+    let sigil = match segments.next()? {
+        "" => return Some(String::new()),
+        "u" => '@',
+        "r" => '#',
+        "roomid" => '!',
+        _ => return None,
+    };
+
+    match segments.next().filter(|id| !id.is_empty()) {
+        Some(id) => Some(format!("{}{}", sigil, id)),
+        None => Some(String::new()),
+    }
+}
+
+/// Shows the channel or nick along with the server, e.g. "irc.libera.chat · #rust". Channels can be in the
+/// path, with or without the #, or in the fragment when the # wasn't encoded.
+fn irc_target(url: &Url) -> Option<Vec<Segment>> {
+    let mut server = url.host_str()?.to_string();
+    if let Some(port) = url.port() {
+        server.push(':');
+        server.push_str(&port.to_string());
+    }
+
+    let path = decoded_path(url);
+    let path = path.trim_matches('/');
+    let target = if path.is_empty() {
+        url.fragment()
+            .map(|fragment| format!("#{}", fragment))
+            .unwrap_or_default()
+    } else {
+        path.to_string()
+    };
+
+    // Flags like ",isnick" or ",needpass" follow the target
+    let mut parts = target.split(',');
+    let name = parts.next().unwrap_or("");
+    let is_nick = parts.any(|flag| flag == "isnick");
+
+    let name = if name.is_empty() || is_nick || name.starts_with(&['#', '&'][..]) {
+        name.to_string()
+    } else {
+        format!("#{}", name)
+    };
+
+    let mut label = vec![Segment::Host(server)];
+    if !name.is_empty() {
+        label.push(Segment::Kept(LABEL_SEPARATOR.to_string()));
+        label.push(Segment::User(name));
+    }
+    Some(label)
+}
+
+/// "37.786971,-122.399677;u=35" becomes "📍 37.786971, -122.399677", parameters are dropped.
+fn coordinates(path: &str) -> String {
+    let coordinates = path.split(';').next().unwrap_or("");
+    format!(
+        "{}{}",
+        PLACE_PREFIX,
+        coordinates.split(',').collect::<Vec<&str>>().join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::segment;
+    use super::*;
+
+    fn label(url_string: &str) -> Option<String> {
+        scheme_label(&Url::parse(url_string).unwrap())
+            .unwrap()
+            .map(|label| segment::join(&label))
+    }
+
+    #[test]
+    fn should_show_email_addresses() {
+        assert_eq!(
+            label("mailto:me@example.com?subject=Hello").unwrap(),
+            "✉ me@example.com"
+        );
+        assert_eq!(
+            label("mailto:d%C3%ADego@example.com").unwrap(),
+            "✉ díego@example.com"
+        );
+    }
+
+    #[test]
+    fn should_group_phone_numbers_with_spaces() {
+        assert_eq!(label("tel:+44-20-7946-0958").unwrap(), "+44 20 7946 0958");
+        assert_eq!(
+            label("tel:+1-(555)-0100;ext=12;phone-context=example.com").unwrap(),
+            "+1 555 0100 ext. 12"
+        );
+    }
+
+    #[test]
+    fn should_show_xmpp_addresses() {
+        assert_eq!(
+            label("xmpp:romeo@montague.net?message").unwrap(),
+            "romeo@montague.net"
+        );
+    }
+
+    #[test]
+    fn should_show_matrix_identifiers() {
+        assert_eq!(
+            label("matrix:u/alice:matrix.org").unwrap(),
+            "@alice:matrix.org"
+        );
+        assert_eq!(
+            label("matrix:r/rust:matrix.org/e/event?action=join").unwrap(),
+            "#rust:matrix.org"
+        );
+        assert_eq!(
+            label("matrix:roomid/abc:matrix.org").unwrap(),
+            "!abc:matrix.org"
+        );
+        assert_eq!(label("matrix:x/abc:matrix.org"), None);
+    }
+
+    #[test]
+    fn should_show_irc_channels_and_nicks() {
+        assert_eq!(
+            label("irc://irc.libera.chat/rust").unwrap(),
+            "irc.libera.chat · #rust"
+        );
+        assert_eq!(
+            label("ircs://irc.libera.chat:6697/#rust").unwrap(),
+            "irc.libera.chat:6697 · #rust"
+        );
+        assert_eq!(
+            label("irc://irc.libera.chat/diegov,isnick").unwrap(),
+            "irc.libera.chat · diegov"
+        );
+        assert_eq!(label("irc://irc.libera.chat").unwrap(), "irc.libera.chat");
+    }
+
+    #[test]
+    fn should_show_coordinates() {
+        assert_eq!(
+            label("geo:37.786971,-122.399677;u=35").unwrap(),
+            "📍 37.786971, -122.399677"
+        );
+    }
+
+    #[test]
+    fn should_reject_addresses_that_are_missing() {
+        for url_string in &[
+            "tel:",
+            "mailto:",
+            "mailto:?subject=Hello",
+            "matrix:",
+            "matrix:u/",
+            "irc:",
+        ] {
+            let url = Url::parse(url_string).unwrap();
+            assert_eq!(
+                scheme_label(&url),
+                Err(AbbreviateError::MissingAddress {
+                    url: url.to_string()
+                }),
+                "{}",
+                url_string
+            );
+        }
+    }
+
+    #[test]
+    fn should_ignore_other_schemes() {
+        assert_eq!(label("https://example.com/"), None);
+        assert_eq!(label("ftp://example.com/"), None);
+    }
+}
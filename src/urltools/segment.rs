@@ -0,0 +1,105 @@
+/// A piece of an abbreviation, with what it is so it can be shown differently.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// The host and port, or the name of a known platform.
+    Host(String),
+    /// Text shown as it is in the URL: path components, separators, the query...
+    Kept(String),
+    /// A path component or subdomain matching one of the names.
+    NameMatch(String),
+    /// A user name in a known platform, a fediverse handle or a contact address. Case matters in these.
+    User(String),
+    /// The ellipsis that replaced part of the URL, and what it replaced.
+    Elided { text: String, original: String },
+}
+
+impl Segment {
+    /// The text shown for this segment.
+    pub fn text(&self) -> &str {
+        match self {
+            Segment::Host(text)
+            | Segment::Kept(text)
+            | Segment::NameMatch(text)
+            | Segment::User(text)
+            | Segment::Elided { text, .. } => text,
+        }
+    }
+}
+
+/// The segments as plain text.
+pub fn join(segments: &[Segment]) -> String {
+    segments.iter().map(Segment::text).collect()
+}
+
+/// Adds a segment, merging it into the last one if they're the same kind of text. Empty segments are skipped.
+pub fn push(segments: &mut Vec<Segment>, segment: Segment) {
+    if segment.text().is_empty() {
+        return;
+    }
+
+    match (segments.last_mut(), &segment) {
+        (Some(Segment::Host(last)), Segment::Host(text))
+        | (Some(Segment::Kept(last)), Segment::Kept(text)) => last.push_str(text),
+        _ => segments.push(segment),
+    }
+}
+
+/// Removes the characters from the end of the plain text at the end, dropping the segments it leaves empty.
+/// Hosts, names and ellipses are left as they are.
+pub fn trim_trailing(segments: &mut Vec<Segment>, characters: &[char]) {
+    while let Some(Segment::Kept(text)) = segments.last_mut() {
+        let trimmed_length = text.trim_end_matches(characters).len();
+        text.truncate(trimmed_length);
+
+        if !text.is_empty() {
+            break;
+        }
+        segments.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_should_merge_plain_text() {
+        let mut segments = vec![];
+        push(&mut segments, Segment::Host("example".to_string()));
+        push(&mut segments, Segment::Host(".com".to_string()));
+        push(&mut segments, Segment::Kept("/".to_string()));
+        push(&mut segments, Segment::NameMatch("john".to_string()));
+        push(&mut segments, Segment::NameMatch("doe".to_string()));
+        push(&mut segments, Segment::Kept("".to_string()));
+
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Host("example.com".to_string()),
+                Segment::Kept("/".to_string()),
+                Segment::NameMatch("john".to_string()),
+                Segment::NameMatch("doe".to_string()),
+            ]
+        );
+        assert_eq!(join(&segments), "example.com/johndoe");
+    }
+
+    #[test]
+    fn should_trim_trailing_separators_across_segments() {
+        let mut segments = vec![
+            Segment::Host("example.com".to_string()),
+            Segment::Kept("/".to_string()),
+            Segment::NameMatch("doe".to_string()),
+            Segment::Kept("-".to_string()),
+        ];
+        trim_trailing(&mut segments, &['/', '-']);
+        assert_eq!(segments.len(), 3);
+
+        let mut segments = vec![
+            Segment::Host("example.com".to_string()),
+            Segment::Kept("/".to_string()),
+        ];
+        trim_trailing(&mut segments, &['/']);
+        assert_eq!(segments, vec![Segment::Host("example.com".to_string())]);
+    }
+}
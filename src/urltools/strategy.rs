@@ -0,0 +1,240 @@
+use super::segment::{self, Segment};
+use super::{AbbreviateError, Measure, Options};
+use unicode_segmentation::UnicodeSegmentation;
+use url::Url;
+
+/// What a strategy gets to work with, only when the URL doesn't fit as it is.
+pub struct Input<'a> {
+    /// The URL after the clean up common to all strategies, e.g. without trailing slash.
+    pub url: &'a Url,
+    /// Host and path as we'd show them if they fit.
+    pub text: &'a str,
+    pub names: &'a [&'a str],
+    /// Length available for the host and path, once the query and fragment we show are accounted for.
+    pub max_length: usize,
+}
+
+/// A way to shorten the host and path of a URL to fit in a length budget.
+pub trait Strategy {
+    fn shorten(&self, input: &Input, options: &Options) -> Result<Vec<Segment>, AbbreviateError>;
+}
+
+/// Cuts the middle of the text, keeping both ends, e.g. "example.com/use...abcdef".
+pub struct MiddleEllipsis;
+
+/// Cuts the text at one end.
+pub enum Truncate {
+    /// Keeps the start of the text, e.g. "example.com/users/...".
+    KeepStart,
+    /// Keeps the end of the text, e.g. ".../users/abcdef".
+    KeepEnd,
+}
+
+impl Strategy for MiddleEllipsis {
+    fn shorten(&self, input: &Input, options: &Options) -> Result<Vec<Segment>, AbbreviateError> {
+        let available = input
+            .max_length
+            .saturating_sub(options.measure.length_of(&options.ellipsis));
+        // The start usually has more information, so it gets the odd character
+        let start_length = available - available / 2;
+
+        let start = take_graphemes(input.text.graphemes(true), start_length, options.measure);
+        let end = take_graphemes(
+            input.text.graphemes(true).rev(),
+            available / 2,
+            options.measure,
+        );
+
+        let start = start.concat();
+        let end: String = end.into_iter().rev().collect();
+        Ok(elide_between(input.text, &start, &end, options))
+    }
+}
+
+impl Strategy for Truncate {
+    fn shorten(&self, input: &Input, options: &Options) -> Result<Vec<Segment>, AbbreviateError> {
+        let available = input
+            .max_length
+            .saturating_sub(options.measure.length_of(&options.ellipsis));
+
+        let result = match self {
+            Truncate::KeepStart => {
+                let start =
+                    take_graphemes(input.text.graphemes(true), available, options.measure).concat();
+                elide_between(input.text, &start, "", options)
+            }
+            Truncate::KeepEnd => {
+                let end =
+                    take_graphemes(input.text.graphemes(true).rev(), available, options.measure);
+                let end: String = end.into_iter().rev().collect();
+                elide_between(input.text, "", &end, options)
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+/// Keeps the start and end of the text, which don't overlap, replacing what's between them with the ellipsis.
+fn elide_between(text: &str, start: &str, end: &str, options: &Options) -> Vec<Segment> {
+    let mut result = vec![];
+    if start.len() + end.len() >= text.len() {
+        // Only when the text already fits, there's nothing to elide
+        result.push(Segment::Kept(text.to_string()));
+        return result;
+    }
+
+    segment::push(&mut result, Segment::Kept(start.to_string()));
+    result.push(Segment::Elided {
+        text: options.ellipsis.clone(),
+        original: text[start.len()..text.len() - end.len()].to_string(),
+    });
+    segment::push(&mut result, Segment::Kept(end.to_string()));
+    result
+}
+
+fn take_graphemes<'a, I: Iterator<Item = &'a str>>(
+    graphemes: I,
+    max_length: usize,
+    measure: Measure,
+) -> Vec<&'a str> {
+    let mut length = 0;
+
+    graphemes
+        .take_while(|grapheme| {
+            length += measure.length_of(grapheme);
+            length <= max_length
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shorten<S: Strategy>(
+        strategy: S,
+        text: &str,
+        max_length: usize,
+        measure: Measure,
+    ) -> String {
+        let url = Url::parse("http://example.com").unwrap();
+        let input = Input {
+            url: &url,
+            text,
+            names: &[],
+            max_length,
+        };
+        let options = Options {
+            measure,
+            ..Options::default()
+        };
+
+        segment::join(&strategy.shorten(&input, &options).unwrap())
+    }
+
+    #[test]
+    fn should_keep_elided_text() {
+        let url = Url::parse("http://example.com").unwrap();
+        let input = Input {
+            url: &url,
+            text: "example.com/users/abcdef",
+            names: &[],
+            max_length: 16,
+        };
+
+        assert_eq!(
+            MiddleEllipsis.shorten(&input, &Options::default()).unwrap(),
+            vec![
+                Segment::Kept("example".to_string()),
+                Segment::Elided {
+                    text: "...".to_string(),
+                    original: ".com/users/".to_string()
+                },
+                Segment::Kept("abcdef".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn middle_ellipsis_should_keep_both_ends() {
+        assert_eq!(
+            shorten(
+                MiddleEllipsis,
+                "example.com/users/abcdef",
+                16,
+                Measure::Graphemes
+            ),
+            "example...abcdef"
+        );
+        assert_eq!(
+            shorten(
+                MiddleEllipsis,
+                "example.com/users/abcdef",
+                17,
+                Measure::Graphemes
+            ),
+            "example.../abcdef"
+        );
+    }
+
+    #[test]
+    fn truncate_should_keep_start() {
+        assert_eq!(
+            shorten(
+                Truncate::KeepStart,
+                "example.com/users/abcdef",
+                16,
+                Measure::Graphemes
+            ),
+            "example.com/u..."
+        );
+    }
+
+    #[test]
+    fn truncate_should_keep_end() {
+        assert_eq!(
+            shorten(
+                Truncate::KeepEnd,
+                "example.com/users/abcdef",
+                16,
+                Measure::Graphemes
+            ),
+            ".../users/abcdef"
+        );
+    }
+
+    #[test]
+    fn truncation_should_not_split_wide_characters() {
+        assert_eq!(
+            shorten(
+                Truncate::KeepStart,
+                "example.com/日本語",
+                17,
+                Measure::DisplayWidth
+            ),
+            "example.com/日..."
+        );
+        assert_eq!(
+            shorten(
+                Truncate::KeepStart,
+                "example.com/cafe\u{301}s",
+                19,
+                Measure::Graphemes
+            ),
+            "example.com/cafe\u{301}..."
+        );
+    }
+
+    #[test]
+    fn truncation_should_only_leave_ellipsis_if_nothing_fits() {
+        assert_eq!(
+            shorten(MiddleEllipsis, "example.com", 2, Measure::Graphemes),
+            "..."
+        );
+        assert_eq!(
+            shorten(Truncate::KeepEnd, "example.com", 3, Measure::Graphemes),
+            "..."
+        );
+    }
+}